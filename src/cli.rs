@@ -1,8 +1,22 @@
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::PathBuf;
 use std::str::FromStr;
 
-use clap::{Args, Parser, Subcommand};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
 
-use crate::config::{DatabaseConnection, DatabaseType};
+use crate::completion::CompletionShell;
+use crate::config::{
+    DatabaseConnection, DatabaseType, MysqlProtocol, OnConflict, PasswordSource, PresetFlags,
+    SslMode,
+};
+use crate::db::DumpFormat;
+use crate::dsn::Dsn;
+use crate::error::AppError;
+use crate::validation::{
+    validate_alias, validate_client_option, validate_container_name, validate_database_name,
+    validate_username, warn_port_for_type,
+};
 
 /// CLI tool for easily connecting to Docker database containers
 #[derive(Debug, Parser)]
@@ -12,8 +26,39 @@ use crate::config::{DatabaseConnection, DatabaseType};
     version
 )]
 pub struct Cli {
+    /// Save the config even if it was written by a newer version of ddcl
+    #[arg(long, global = true)]
+    pub force: bool,
+
+    /// Skip container, username, and database name validation (only for trusted inputs)
+    #[arg(long, global = true)]
+    pub no_validate: bool,
+
+    /// Seconds to wait for docker queries (e.g. `docker ps`) before giving up
+    #[arg(long, global = true, default_value_t = 10, value_name = "SECS",
+          value_parser = clap::value_parser!(u64).range(1..))]
+    pub timeout: u64,
+
+    /// Use `config.<PROFILE>.yaml` instead of `config.yaml`
+    #[arg(long, global = true, value_name = "PROFILE")]
+    pub profile: Option<String>,
+
+    /// Encrypt the config file with a master password (prompted unless DDCL_MASTER_KEY is set)
+    #[arg(long, global = true)]
+    pub encrypted: bool,
+
+    /// Log connect attempts, container checks, and each docker command before it runs
+    /// (passwords masked)
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    /// Log output format: human-readable text, or one JSON object per line for log pipelines
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub log_format: LogFormat,
+
+    /// Without a subcommand, pick a saved connection from a menu and connect to it
     #[command(subcommand)]
-    pub command: Commands,
+    pub command: Option<Commands>,
 }
 
 /// Subcommands
@@ -27,26 +72,155 @@ pub enum Commands {
     #[command(name = "add", about = "Add a connection configuration")]
     Add(AddArgs),
 
+    /// Edit an existing connection configuration
+    #[command(name = "edit", about = "Edit an existing connection configuration")]
+    Edit(EditArgs),
+
+    /// Rename a connection alias
+    #[command(name = "rename", about = "Rename a connection alias")]
+    Rename(RenameArgs),
+
+    /// Copy a connection configuration to a new alias
+    #[command(
+        name = "duplicate",
+        about = "Copy a connection configuration to a new alias"
+    )]
+    Duplicate(DuplicateArgs),
+
     /// Remove a connection configuration
     #[command(name = "remove", about = "Remove a connection configuration")]
     Remove(RemoveArgs),
 
+    /// Remove every connection configuration
+    #[command(name = "clear", about = "Remove every connection configuration")]
+    Clear(ClearArgs),
+
     /// Display a list of connection configurations
     #[command(name = "list", about = "Display a list of connection configurations")]
-    List,
+    List(ListArgs),
+
+    /// List running database containers
+    #[command(name = "detect", about = "List running database containers")]
+    Detect(DetectArgs),
+
+    /// List the most recently used aliases
+    #[command(name = "recent", about = "List the most recently used aliases")]
+    Recent(RecentArgs),
+
+    /// Toggle a connection as favorite
+    #[command(name = "favorite", about = "Toggle a connection as favorite")]
+    Favorite(FavoriteArgs),
+
+    /// Show or set the connection used by `connect` without arguments
+    #[command(
+        name = "default",
+        about = "Show or set the connection used by `connect` without arguments"
+    )]
+    Default(DefaultArgs),
+
+    /// Manage connect-time presets
+    #[command(name = "preset", about = "Manage connect-time presets")]
+    Preset(PresetArgs),
+
+    /// Dump a database to a file
+    #[command(name = "dump", about = "Dump a database to a file")]
+    Dump(DumpArgs),
+
+    /// Show the logs of a connection's container
+    #[command(name = "logs", about = "Show the logs of a connection's container")]
+    Logs(LogsArgs),
+
+    /// Print a connection URI for use in other tools
+    #[command(name = "uri", about = "Print a connection URI for use in other tools")]
+    Uri(UriArgs),
+
+    /// Run a single query against a connection and print the output
+    #[command(
+        name = "exec",
+        about = "Run a single query against a connection and print the output"
+    )]
+    Exec(ExecArgs),
+
+    /// Check that a saved connection accepts connections
+    #[command(
+        name = "test",
+        about = "Check that a saved connection accepts connections"
+    )]
+    Test(TestArgs),
+
+    /// Show the details of a connection configuration
+    #[command(
+        name = "show",
+        about = "Show the details of a connection configuration"
+    )]
+    Show(ShowArgs),
+
+    /// Export connections as JSON or a shell script
+    #[command(
+        name = "export",
+        about = "Export connections as JSON or a shell script"
+    )]
+    Export(ExportArgs),
+
+    /// Import connections from a JSON or YAML file
+    #[command(name = "import", about = "Import connections from a JSON or YAML file")]
+    Import(ImportArgs),
+
+    /// List config profiles
+    #[command(name = "profiles", about = "List config profiles")]
+    Profiles,
+
+    /// Maintain the config file
+    #[command(name = "config", about = "Maintain the config file")]
+    Config(ConfigArgs),
+
+    /// Generate shell completion scripts
+    #[command(
+        name = "completion",
+        visible_alias = "completions",
+        about = "Generate shell completion scripts"
+    )]
+    Completion(CompletionArgs),
+
+    /// Print saved aliases for shell completion
+    #[command(name = "__complete-aliases", hide = true)]
+    CompleteAliases,
+
+    /// Check every saved connection for invalid values
+    #[command(
+        name = "validate",
+        about = "Check every saved connection for invalid values"
+    )]
+    Validate,
+
+    /// Diagnose common setup problems
+    #[command(
+        name = "doctor",
+        about = "Check Docker, the config file, and the terminal for common setup problems"
+    )]
+    Doctor,
+
+    /// Check a name against the validation rules
+    #[command(
+        name = "check-name",
+        about = "Check a name against the validation rules",
+        hide = true
+    )]
+    CheckName(CheckNameArgs),
 }
 
 /// Connect command arguments
-#[derive(Debug, Args)]
+#[derive(Debug, Default, Args)]
 pub struct ConnectArgs {
-    /// Alias name (if not specified, container name and other arguments are required)
+    /// Alias name (if not specified, container name and other arguments are required, or the
+    /// default connection is used when none are given)
     pub alias: Option<String>,
 
-    /// Container name (when not using alias)
+    /// Container name or ID prefix (when not using alias)
     #[arg(short, long)]
     pub container: Option<String>,
 
-    /// Database type (postgres, mysql, or mongodb)
+    /// Database type (postgres, mysql, mongodb, sqlite, cockroachdb, or opensearch)
     #[arg(short, long)]
     pub db_type: Option<String>,
 
@@ -54,53 +228,203 @@ pub struct ConnectArgs {
     #[arg(short, long)]
     pub user: Option<String>,
 
-    /// Password
+    /// Password (visible in shell history and `ps`; prefer --password-stdin)
     #[arg(short, long)]
     pub password: Option<String>,
 
-    /// Database name
+    /// Read the password from the first line of stdin
+    #[arg(long, conflicts_with_all = ["password", "interactive"])]
+    pub password_stdin: bool,
+
+    /// Database name (file path inside the container for SQLite)
     #[arg(short = 'n', long)]
     pub database: Option<String>,
 
     /// Port number
     #[arg(short = 'P', long)]
     pub port: Option<u16>,
+
+    /// Docker label selector resolving to the container (e.g. app=orders-db)
+    #[arg(long = "label")]
+    pub container_label: Option<String>,
+
+    /// Docker daemon the container runs on, e.g. ssh://user@db-host (sets DOCKER_HOST)
+    #[arg(long)]
+    pub docker_host: Option<String>,
+
+    /// MySQL transport protocol: tcp (127.0.0.1 on the port) or socket
+    #[arg(long)]
+    pub protocol: Option<MysqlProtocol>,
+
+    /// PostgreSQL TLS mode (disable, allow, prefer, require, verify-ca, verify-full)
+    #[arg(long)]
+    pub ssl_mode: Option<SslMode>,
+
+    /// Connect to MongoDB or OpenSearch over TLS
+    #[arg(long)]
+    pub tls: bool,
+
+    /// MongoDB authentication database (e.g. admin) when the user is defined outside --database
+    #[arg(long)]
+    pub auth_database: Option<String>,
+
+    /// Working directory inside the container (absolute path)
+    #[arg(long = "workdir")]
+    pub working_dir: Option<String>,
+
+    /// OS user inside the container to run the client as (`docker exec -u`), not the DB user
+    #[arg(long)]
+    pub exec_user: Option<String>,
+
+    /// Environment variable for the client as KEY=VALUE (repeatable), e.g. `--env PGTZ=UTC`
+    #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pub env: Vec<(String, String)>,
+
+    /// Client option passed as `--KEY VALUE` (repeatable), e.g. `--option connect-timeout=5`
+    #[arg(long = "option", value_name = "KEY=VALUE", value_parser = parse_client_option)]
+    pub options: Vec<(String, String)>,
+
+    /// libpq-style connection string (e.g. "host=orders-db dbname=orders user=app")
+    ///
+    /// Explicit flags take precedence over DSN values
+    #[arg(long)]
+    pub dsn: Option<Dsn>,
+
+    /// Command run instead of the default client, e.g. "python -m myshell" (split on whitespace)
+    #[arg(long, allow_hyphen_values = true)]
+    pub entrypoint: Option<String>,
+
+    /// Docker Compose service to connect to, waiting until it is healthy
+    #[arg(long)]
+    pub compose_service: Option<String>,
+
+    /// Seconds to wait for the Compose service to become healthy
+    #[arg(long, default_value_t = 60, requires = "compose_service")]
+    pub wait_timeout: u64,
+
+    /// Treat the container name (or the alias's container) as a Docker Compose service name
+    #[arg(long, conflicts_with_all = ["compose_service", "container_label"])]
+    pub compose: bool,
+
+    /// Directory containing the Compose project (defaults to the current directory)
+    #[arg(long, value_name = "DIR")]
+    pub project_directory: Option<PathBuf>,
+
+    /// Wait up to this many seconds for a stopped or missing container to start
+    #[arg(long, value_name = "SECS")]
+    pub wait: Option<u64>,
+
+    /// With --wait, also wait until the database accepts connections (the `test` probe)
+    #[arg(long, requires = "wait")]
+    pub wait_ready: bool,
+
+    /// Copy the session output to a file (runs the client without a TTY)
+    #[arg(long, value_name = "PATH")]
+    pub record: Option<PathBuf>,
+
+    /// Run the client without a TTY (`docker exec -i`), e.g. in CI or with piped input
+    ///
+    /// Also applied automatically when stdin is not a terminal
+    #[arg(long)]
+    pub no_tty: bool,
+
+    /// Print the docker command instead of running it
+    #[arg(long, conflicts_with = "record")]
+    pub dry_run: bool,
+
+    /// Copy the docker command to the clipboard instead of running it
+    #[arg(long, conflicts_with_all = ["record", "dry_run"])]
+    pub copy_to_clipboard: bool,
+
+    /// Name of a preset to apply before connecting
+    #[arg(long)]
+    pub preset: Option<String>,
+
+    /// Detect the database port from `docker port` when no port is set
+    #[arg(long)]
+    pub detect_port: bool,
+
+    /// Enter the connection interactively, then offer to save it after disconnecting
+    #[arg(short, long, conflicts_with = "alias")]
+    pub interactive: bool,
+
+    /// Do not offer to save an interactive connection
+    #[arg(long, requires = "interactive")]
+    pub no_save: bool,
 }
 
 impl ConnectArgs {
+    /// Whether any flag describing a connection without an alias was given
+    pub fn has_connection_params(&self) -> bool {
+        self.container.is_some()
+            || self.db_type.is_some()
+            || self.user.is_some()
+            || self.dsn.is_some()
+            || self.container_label.is_some()
+            || self.compose_service.is_some()
+    }
+
     /// Convert connection info to DatabaseConnection
     pub fn to_connection(&self) -> Option<DatabaseConnection> {
-        if let (Some(container), Some(db_type_str), Some(user)) =
-            (&self.container, &self.db_type, &self.user)
-        {
-            if let Ok(db_type) = DatabaseType::from_str(db_type_str) {
-                return Some(DatabaseConnection {
-                    db_type,
-                    container: container.clone(),
-                    user: user.clone(),
-                    password: self.password.clone(),
-                    database: self.database.clone(),
-                    port: self.port,
-                    options: None,
-                });
-            }
-        }
-        None
+        let dsn = self.dsn.clone().unwrap_or_default();
+        let container = self.container.clone().or(dsn.host);
+        // The container of a Compose service is resolved at connect time
+        if container.is_none() && self.container_label.is_none() && self.compose_service.is_none() {
+            return None;
+        }
+
+        let db_type = match &self.db_type {
+            Some(db_type_str) => DatabaseType::from_str(db_type_str).ok()?,
+            // DSNs are libpq syntax, so assume PostgreSQL unless told otherwise
+            None if self.dsn.is_some() => DatabaseType::PostgreSQL,
+            None => return None,
+        };
+
+        let user = match self.user.clone().or(dsn.user) {
+            Some(user) => user,
+            // SQLite files have no users
+            None if !db_type.requires_user() => String::new(),
+            None => return None,
+        };
+
+        let port = self.port.or(dsn.port);
+        warn_port_for_type(port, &db_type);
+
+        Some(DatabaseConnection {
+            db_type,
+            container: container.unwrap_or_default(),
+            user,
+            password: self.password.clone().or(dsn.password),
+            database: self.database.clone().or(dsn.database),
+            port,
+            options: pairs_map(&self.options),
+            container_label: self.container_label.clone(),
+            protocol: self.protocol,
+            ssl_mode: self.ssl_mode,
+            tls: self.tls,
+            auth_database: self.auth_database.clone(),
+            entrypoint: self.entrypoint.as_deref().and_then(parse_entrypoint),
+            working_dir: self.working_dir.clone(),
+            exec_user: self.exec_user.clone(),
+            env: pairs_map(&self.env),
+            docker_host: self.docker_host.clone(),
+            ..Default::default()
+        })
     }
 }
 
 /// Add command arguments
-#[derive(Debug, Args)]
+#[derive(Debug, Default, Args)]
 pub struct AddArgs {
     /// Alias name
     #[arg(required = false)]
     pub alias: Option<String>,
 
-    /// Container name
+    /// Container name or ID prefix
     #[arg(short, long)]
     pub container: Option<String>,
 
-    /// Database type (postgres, mysql, or mongodb)
+    /// Database type (postgres, mysql, mongodb, sqlite, cockroachdb, or opensearch)
     #[arg(short, long)]
     pub db_type: Option<String>,
 
@@ -108,11 +432,15 @@ pub struct AddArgs {
     #[arg(short, long)]
     pub user: Option<String>,
 
-    /// Password
+    /// Password (visible in shell history and `ps`; prefer --password-stdin)
     #[arg(short, long)]
     pub password: Option<String>,
 
-    /// Database name
+    /// Read the password from the first line of stdin
+    #[arg(long, conflicts_with_all = ["password", "interactive"])]
+    pub password_stdin: bool,
+
+    /// Database name (file path inside the container for SQLite)
     #[arg(short = 'n', long)]
     pub database: Option<String>,
 
@@ -120,6 +448,82 @@ pub struct AddArgs {
     #[arg(short = 'P', long)]
     pub port: Option<u16>,
 
+    /// Docker label selector resolving to the container (e.g. app=orders-db)
+    #[arg(long = "label")]
+    pub container_label: Option<String>,
+
+    /// Docker daemon the container runs on, e.g. ssh://user@db-host (sets DOCKER_HOST)
+    #[arg(long)]
+    pub docker_host: Option<String>,
+
+    /// MySQL transport protocol: tcp (127.0.0.1 on the port) or socket
+    #[arg(long)]
+    pub protocol: Option<MysqlProtocol>,
+
+    /// PostgreSQL TLS mode (disable, allow, prefer, require, verify-ca, verify-full)
+    #[arg(long)]
+    pub ssl_mode: Option<SslMode>,
+
+    /// Connect to MongoDB or OpenSearch over TLS
+    #[arg(long)]
+    pub tls: bool,
+
+    /// MongoDB authentication database (e.g. admin) when the user is defined outside --database
+    #[arg(long)]
+    pub auth_database: Option<String>,
+
+    /// Working directory inside the container (absolute path)
+    #[arg(long = "workdir")]
+    pub working_dir: Option<String>,
+
+    /// OS user inside the container to run the client as (`docker exec -u`), not the DB user
+    #[arg(long)]
+    pub exec_user: Option<String>,
+
+    /// Extra argument passed to `docker exec` (not the database client); repeat for each
+    /// argument, e.g. `--exec-option -e --exec-option TZ=UTC`
+    #[arg(long = "exec-option", value_name = "ARG", allow_hyphen_values = true)]
+    pub exec_options: Vec<String>,
+
+    /// Tag for grouping connections, e.g. prod (repeatable)
+    #[arg(short, long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Environment variable for the client as KEY=VALUE (repeatable), e.g. `--env PGTZ=UTC`
+    #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pub env: Vec<(String, String)>,
+
+    /// Client option passed as `--KEY VALUE` (repeatable), e.g. `--option connect-timeout=5`
+    #[arg(long = "option", value_name = "KEY=VALUE", value_parser = parse_client_option)]
+    pub options: Vec<(String, String)>,
+
+    /// libpq-style connection string (e.g. "host=orders-db dbname=orders user=app")
+    ///
+    /// Explicit flags take precedence over DSN values
+    #[arg(long)]
+    pub dsn: Option<Dsn>,
+
+    /// Command run instead of the default client, e.g. "python -m myshell" (split on whitespace)
+    #[arg(long, allow_hyphen_values = true)]
+    pub entrypoint: Option<String>,
+
+    /// Path of the client executable inside the container (e.g. /opt/pg/bin/psql)
+    #[arg(long)]
+    pub client_path: Option<String>,
+
+    /// Host .env file providing USER/PASSWORD/DATABASE at connect time
+    #[arg(long)]
+    pub env_file: Option<String>,
+
+    /// Require typing the alias before destructive operations
+    #[arg(long)]
+    pub protected: bool,
+
+    /// Where the password is kept: plain (config file), keyring (OS keychain), or env
+    /// (--password names an environment variable)
+    #[arg(long)]
+    pub password_source: Option<PasswordSource>,
+
     /// Use interactive mode
     #[arg(short, long)]
     pub interactive: bool,
@@ -127,6 +531,18 @@ pub struct AddArgs {
     /// Use auto-detect mode (auto-detect running DB containers)
     #[arg(short = 'a', long)]
     pub auto_detect: bool,
+
+    /// Replace an existing connection with the same alias
+    #[arg(long)]
+    pub overwrite: bool,
+
+    /// Print the resulting connection as YAML (password masked) instead of saving it
+    #[arg(long, visible_alias = "print")]
+    pub no_save: bool,
+
+    /// Do not warn when the password is stored in plaintext
+    #[arg(short, long)]
+    pub quiet: bool,
 }
 
 impl AddArgs {
@@ -139,39 +555,687 @@ impl AddArgs {
             return Err("Missing required information in interactive mode".to_string());
         }
 
-        let db_type_str = match &self.db_type {
-            Some(db_type) => db_type,
+        let dsn = self.dsn.clone().unwrap_or_default();
+
+        let db_type = match &self.db_type {
+            Some(db_type_str) => DatabaseType::from_str(db_type_str)
+                .map_err(|e| format!("Database type parse error: {}", e))?,
+            // DSNs are libpq syntax, so assume PostgreSQL unless told otherwise
+            None if self.dsn.is_some() => DatabaseType::PostgreSQL,
             None => return Err("Database type not specified".to_string()),
         };
 
-        let db_type = DatabaseType::from_str(db_type_str)
-            .map_err(|e| format!("Database type parse error: {}", e))?;
-
-        let container = match &self.container {
-            Some(container) => container.clone(),
-            None => return Err("Container name not specified".to_string()),
+        let container = match (self.container.clone().or(dsn.host), &self.container_label) {
+            (Some(container), _) => container,
+            // The container is resolved from the label at connect time
+            (None, Some(_)) => String::new(),
+            (None, None) => return Err("Container name not specified".to_string()),
         };
 
-        let user = match &self.user {
-            Some(user) => user.clone(),
+        let user = match self.user.clone().or(dsn.user) {
+            Some(user) => user,
+            // SQLite files have no users
+            None if !db_type.requires_user() => String::new(),
             None => return Err("Username not specified".to_string()),
         };
 
+        let port = self.port.or(dsn.port);
+        warn_port_for_type(port, &db_type);
+
         Ok(DatabaseConnection {
             db_type,
             container,
             user,
-            password: self.password.clone(),
-            database: self.database.clone(),
-            port: self.port,
-            options: None,
+            password: self.password.clone().or(dsn.password),
+            database: self.database.clone().or(dsn.database),
+            port,
+            options: pairs_map(&self.options),
+            container_label: self.container_label.clone(),
+            protocol: self.protocol,
+            ssl_mode: self.ssl_mode,
+            tls: self.tls,
+            auth_database: self.auth_database.clone(),
+            entrypoint: self.entrypoint.as_deref().and_then(parse_entrypoint),
+            client_path: self.client_path.clone(),
+            working_dir: self.working_dir.clone(),
+            exec_user: self.exec_user.clone(),
+            exec_options: (!self.exec_options.is_empty()).then(|| self.exec_options.clone()),
+            env: pairs_map(&self.env),
+            docker_host: self.docker_host.clone(),
+            tags: (!self.tags.is_empty()).then(|| self.tags.clone()),
+            env_file: self.env_file.clone(),
+            protected: self.protected,
+            ..Default::default()
         })
     }
 }
 
+/// Edit command arguments
+///
+/// Only the flags that are passed are changed; every other field is kept.
+#[derive(Debug, Default, Args)]
+pub struct EditArgs {
+    /// Alias name to edit
+    pub alias: String,
+
+    /// Container name or ID prefix
+    #[arg(short, long)]
+    pub container: Option<String>,
+
+    /// Database type (postgres, mysql, mongodb, sqlite, cockroachdb, or opensearch)
+    #[arg(short, long)]
+    pub db_type: Option<String>,
+
+    /// Username
+    #[arg(short, long)]
+    pub user: Option<String>,
+
+    /// Password
+    #[arg(short, long)]
+    pub password: Option<String>,
+
+    /// Database name (file path inside the container for SQLite)
+    #[arg(short = 'n', long)]
+    pub database: Option<String>,
+
+    /// Port number
+    #[arg(short = 'P', long)]
+    pub port: Option<u16>,
+
+    /// Docker label selector resolving to the container (e.g. app=orders-db)
+    #[arg(long = "label")]
+    pub container_label: Option<String>,
+
+    /// Docker daemon the container runs on, e.g. ssh://user@db-host (sets DOCKER_HOST)
+    #[arg(long)]
+    pub docker_host: Option<String>,
+
+    /// MySQL transport protocol: tcp (127.0.0.1 on the port) or socket
+    #[arg(long)]
+    pub protocol: Option<MysqlProtocol>,
+
+    /// PostgreSQL TLS mode (disable, allow, prefer, require, verify-ca, verify-full)
+    #[arg(long)]
+    pub ssl_mode: Option<SslMode>,
+
+    /// Connect to MongoDB or OpenSearch over TLS (true or false)
+    #[arg(long)]
+    pub tls: Option<bool>,
+
+    /// MongoDB authentication database (e.g. admin) when the user is defined outside --database
+    #[arg(long)]
+    pub auth_database: Option<String>,
+
+    /// Working directory inside the container (absolute path)
+    #[arg(long = "workdir")]
+    pub working_dir: Option<String>,
+
+    /// OS user inside the container to run the client as (`docker exec -u`), not the DB user
+    #[arg(long)]
+    pub exec_user: Option<String>,
+
+    /// Environment variable for the client as KEY=VALUE (repeatable); replaces an existing value
+    #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pub env: Vec<(String, String)>,
+
+    /// Command run instead of the default client, e.g. "python -m myshell" (split on whitespace)
+    #[arg(long, allow_hyphen_values = true)]
+    pub entrypoint: Option<String>,
+
+    /// Host .env file providing USER/PASSWORD/DATABASE at connect time
+    #[arg(long)]
+    pub env_file: Option<String>,
+
+    /// Require typing the alias before destructive operations (true or false)
+    #[arg(long)]
+    pub protected: Option<bool>,
+
+    /// Edit each field interactively, starting from the current values
+    #[arg(short, long)]
+    pub interactive: bool,
+}
+
+impl EditArgs {
+    /// Apply the passed flags to an existing connection
+    pub fn apply(&self, connection: &mut DatabaseConnection) -> Result<(), String> {
+        if let Some(db_type) = &self.db_type {
+            connection.db_type = DatabaseType::from_str(db_type)
+                .map_err(|e| format!("Database type parse error: {}", e))?;
+        }
+        if let Some(container) = &self.container {
+            connection.container = container.clone();
+        }
+        if let Some(user) = &self.user {
+            connection.user = user.clone();
+        }
+        if let Some(password) = &self.password {
+            connection.password = Some(password.clone());
+        }
+        if let Some(database) = &self.database {
+            connection.database = Some(database.clone());
+        }
+        if let Some(port) = self.port {
+            connection.port = Some(port);
+        }
+        if let Some(label) = &self.container_label {
+            connection.container_label = Some(label.clone());
+        }
+        if let Some(protocol) = self.protocol {
+            connection.protocol = Some(protocol);
+        }
+        if let Some(ssl_mode) = self.ssl_mode {
+            connection.ssl_mode = Some(ssl_mode);
+        }
+        if let Some(tls) = self.tls {
+            connection.tls = tls;
+        }
+        if let Some(auth_database) = &self.auth_database {
+            connection.auth_database = Some(auth_database.clone());
+        }
+        if let Some(working_dir) = &self.working_dir {
+            connection.working_dir = Some(working_dir.clone());
+        }
+        if let Some(exec_user) = &self.exec_user {
+            connection.exec_user = Some(exec_user.clone());
+        }
+        if let Some(docker_host) = &self.docker_host {
+            connection.docker_host = Some(docker_host.clone());
+        }
+        if !self.env.is_empty() {
+            connection
+                .env
+                .get_or_insert_with(HashMap::new)
+                .extend(self.env.iter().cloned());
+        }
+        if let Some(entrypoint) = &self.entrypoint {
+            connection.entrypoint = parse_entrypoint(entrypoint);
+        }
+        if let Some(env_file) = &self.env_file {
+            connection.env_file = Some(env_file.clone());
+        }
+        if let Some(protected) = self.protected {
+            connection.protected = protected;
+        }
+        Ok(())
+    }
+}
+
+/// Rename command arguments
+#[derive(Debug, Args)]
+pub struct RenameArgs {
+    /// Current alias name
+    pub old: String,
+
+    /// New alias name
+    pub new: String,
+}
+
+/// Duplicate command arguments
+#[derive(Debug, Args)]
+pub struct DuplicateArgs {
+    /// Alias to copy
+    pub alias: String,
+
+    /// Alias for the copy
+    pub new_alias: String,
+}
+
 /// Remove command arguments
 #[derive(Debug, Args)]
 pub struct RemoveArgs {
     /// Alias name to remove
     pub alias: String,
+
+    /// Skip the confirmation prompt (required when stdin is not a terminal)
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// Skip the typed confirmation for protected connections
+    #[arg(long)]
+    pub force_protected: bool,
+}
+
+/// Order of `list` output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ListSort {
+    /// Favorites first, then by alias
+    #[default]
+    Alias,
+    /// Most recently used first; never-used connections last
+    Recent,
+}
+
+/// Clear command arguments
+#[derive(Debug, Args)]
+pub struct ClearArgs {
+    /// Skip the confirmation prompt (required when stdin is not a terminal)
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// Also remove protected connections
+    #[arg(long)]
+    pub force_protected: bool,
+}
+
+/// List command arguments
+#[derive(Debug, Default, Args)]
+pub struct ListArgs {
+    /// Show only connections whose container is running
+    #[arg(long, conflicts_with = "stopped")]
+    pub running: bool,
+
+    /// Show only connections whose container is stopped
+    #[arg(long)]
+    pub stopped: bool,
+
+    /// Show only connections pointing at this container
+    #[arg(short, long)]
+    pub container: Option<String>,
+
+    /// Show only connections with this tag
+    #[arg(short, long)]
+    pub tag: Option<String>,
+
+    /// Order of the connections: alias (favorites first) or recent (last used first)
+    #[arg(short, long, value_enum, default_value_t)]
+    pub sort: ListSort,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t)]
+    pub format: ListFormat,
+}
+
+/// Log output format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// env_logger's text lines
+    #[default]
+    Text,
+    /// One JSON object per line, with structured fields
+    Json,
+}
+
+/// List output format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ListFormat {
+    /// Human-readable lines
+    #[default]
+    Table,
+    /// JSON array of connections (never includes passwords)
+    Json,
+}
+
+impl ListArgs {
+    /// Whether a connection with the given container status should be displayed
+    pub fn matches_status(&self, running: bool) -> bool {
+        (!self.running || running) && (!self.stopped || !running)
+    }
+}
+
+/// Detect command arguments
+#[derive(Debug, Args)]
+pub struct DetectArgs {
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = ListFormat::Table)]
+    pub format: ListFormat,
+
+    /// Only show containers of this database type (postgres, mysql, mongodb, cockroachdb, or opensearch)
+    #[arg(short, long, visible_alias = "type")]
+    pub db_type: Option<String>,
+
+    /// Show at most this many containers
+    #[arg(long)]
+    pub limit: Option<usize>,
+}
+
+/// Recent command arguments
+#[derive(Debug, Args)]
+pub struct RecentArgs {
+    /// Maximum number of aliases to show
+    #[arg(short = 'n', long)]
+    pub limit: Option<usize>,
+}
+
+/// Favorite command arguments
+#[derive(Debug, Args)]
+pub struct FavoriteArgs {
+    /// Alias name to toggle
+    pub alias: String,
+}
+
+/// Default command arguments
+#[derive(Debug, Args)]
+pub struct DefaultArgs {
+    /// Alias to use by default (prints the current default if omitted)
+    #[arg(conflicts_with = "clear")]
+    pub alias: Option<String>,
+
+    /// Unset the default connection
+    #[arg(long)]
+    pub clear: bool,
+}
+
+/// Config command arguments
+#[derive(Debug, Args)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommands,
+}
+
+/// Config subcommands
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommands {
+    /// Rewrite the config file in canonical form
+    #[command(
+        name = "normalize",
+        about = "Rewrite the config file in canonical form"
+    )]
+    Normalize,
+}
+
+/// Preset command arguments
+#[derive(Debug, Args)]
+pub struct PresetArgs {
+    #[command(subcommand)]
+    pub command: PresetCommands,
+}
+
+/// Preset subcommands
+#[derive(Debug, Subcommand)]
+pub enum PresetCommands {
+    /// Display a list of presets
+    #[command(name = "list", about = "Display a list of presets")]
+    List,
+
+    /// Add or replace a preset
+    #[command(name = "add", about = "Add or replace a preset")]
+    Add(PresetAddArgs),
+}
+
+/// Preset add command arguments
+#[derive(Debug, Default, Args)]
+pub struct PresetAddArgs {
+    /// Preset name
+    pub name: String,
+
+    /// Database name override
+    #[arg(short = 'n', long)]
+    pub database: Option<String>,
+
+    /// MySQL transport protocol: tcp (127.0.0.1 on the port) or socket
+    #[arg(long)]
+    pub protocol: Option<MysqlProtocol>,
+
+    /// Client option as key=value (repeatable)
+    #[arg(short, long = "option", value_parser = parse_key_value)]
+    pub options: Vec<(String, String)>,
+
+    /// Extra argument passed to the database client (repeatable)
+    #[arg(long = "arg", allow_hyphen_values = true)]
+    pub extra_args: Vec<String>,
+
+    /// Open sessions read-only (PostgreSQL, MySQL, and SQLite)
+    #[arg(long)]
+    pub read_only: bool,
+
+    /// Turn off the client's pager (psql and mysql)
+    #[arg(long)]
+    pub no_pager: bool,
+}
+
+impl PresetAddArgs {
+    /// Convert arguments to PresetFlags
+    pub fn to_preset(&self) -> PresetFlags {
+        PresetFlags {
+            database: self.database.clone(),
+            protocol: self.protocol,
+            options: (!self.options.is_empty())
+                .then(|| self.options.iter().cloned().collect::<HashMap<_, _>>()),
+            extra_args: (!self.extra_args.is_empty()).then(|| self.extra_args.clone()),
+            read_only: self.read_only,
+            no_pager: self.no_pager,
+        }
+    }
+}
+
+/// Split an `--entrypoint` command into argv (`None` when it is blank)
+pub fn parse_entrypoint(s: &str) -> Option<Vec<String>> {
+    let argv: Vec<String> = s.split_whitespace().map(str::to_string).collect();
+    (!argv.is_empty()).then_some(argv)
+}
+
+/// Read a password from the first line of `reader` (`--password-stdin`)
+pub fn read_password_line<R: BufRead>(mut reader: R) -> Result<String, AppError> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    let password = line.trim_end_matches(['\r', '\n']);
+    if password.is_empty() {
+        return Err(AppError::ValidationError(
+            "No password received on stdin".to_string(),
+        ));
+    }
+    Ok(password.to_string())
+}
+
+/// Parse a `key=value` pair
+pub fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("Invalid key=value pair: '{}'", s)),
+    }
+}
+
+/// Parse a `--option KEY=VALUE` flag, rejecting names and values unsafe to pass to the client
+pub fn parse_client_option(s: &str) -> Result<(String, String), String> {
+    let (key, value) = parse_key_value(s)?;
+    validate_client_option(&key, &value).map_err(|e| e.to_string())?;
+    Ok((key, value))
+}
+
+/// Collect repeated KEY=VALUE flags (`--env`, `--option`) into a connection map
+pub fn pairs_map(pairs: &[(String, String)]) -> Option<HashMap<String, String>> {
+    (!pairs.is_empty()).then(|| pairs.iter().cloned().collect())
+}
+
+/// Kind of name checked by `check-name`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum NameKind {
+    /// Docker container name
+    Container,
+    /// Database username
+    Username,
+    /// Database name
+    Database,
+    /// Connection alias
+    Alias,
+}
+
+impl NameKind {
+    /// Run the validator for this kind of name
+    pub fn validate(&self, value: &str) -> Result<(), AppError> {
+        match self {
+            NameKind::Container => validate_container_name(value),
+            NameKind::Username => validate_username(value),
+            NameKind::Database => validate_database_name(value),
+            NameKind::Alias => validate_alias(value),
+        }
+    }
+}
+
+/// Check-name command arguments
+#[derive(Debug, Args)]
+pub struct CheckNameArgs {
+    /// Kind of name to check
+    #[arg(value_enum)]
+    pub kind: NameKind,
+
+    /// Value to check
+    pub value: String,
+}
+
+/// Export output format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// The whole config as JSON
+    #[default]
+    Json,
+    /// Shell script with one function per connection
+    Sh,
+}
+
+/// Export command arguments
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t)]
+    pub format: ExportFormat,
+
+    /// File to write to (defaults to stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Drop all passwords so the export can be shared
+    #[arg(long)]
+    pub redact: bool,
+}
+
+/// Import command arguments
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// File to import (`.json`, `.yaml`, or `.yml`), e.g. the output of `ddcl export`
+    pub path: PathBuf,
+
+    /// What to do when an imported alias already exists (skip, overwrite, or error)
+    #[arg(long, default_value = "error")]
+    pub on_conflict: OnConflict,
+
+    /// Keep the pre_connect/post_connect hooks of imported connections (dropped by default)
+    #[arg(long)]
+    pub keep_hooks: bool,
+}
+
+/// Exec command arguments
+#[derive(Debug, Args)]
+#[command(group(ArgGroup::new("input").required(true).args(["query", "sql"])))]
+pub struct ExecArgs {
+    /// Alias name
+    pub alias: String,
+
+    /// Query or command to run (SQL for PostgreSQL/MySQL, a mongosh expression for MongoDB)
+    #[arg(short, long)]
+    pub query: Option<String>,
+
+    /// Query given after `--`, e.g. `ddcl exec pg -- SELECT 1` (words are joined with spaces)
+    #[arg(last = true, value_name = "SQL")]
+    pub sql: Vec<String>,
+
+    /// Print MongoDB results as Extended JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl ExecArgs {
+    /// The query from `--query` or the trailing words after `--`
+    pub fn query(&self) -> String {
+        self.query.clone().unwrap_or_else(|| self.sql.join(" "))
+    }
+}
+
+/// Test command arguments
+#[derive(Debug, Args)]
+pub struct TestArgs {
+    /// Alias name to test
+    pub alias: String,
+}
+
+/// Show command arguments
+#[derive(Debug, Args)]
+pub struct ShowArgs {
+    /// Alias name to show
+    pub alias: String,
+
+    /// Print only the value of a single field (e.g. port), suitable for `$(...)` capture
+    #[arg(long, conflicts_with = "format")]
+    pub field: Option<String>,
+
+    /// Show the password instead of masking it
+    #[arg(long, alias = "show-password")]
+    pub reveal: bool,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = ShowFormat::Text)]
+    pub format: ShowFormat,
+}
+
+/// Show output format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ShowFormat {
+    /// One `field: value` line per set field
+    #[default]
+    Text,
+    /// JSON object with the alias and every set field
+    Json,
+}
+
+/// Dump command arguments
+#[derive(Debug, Args)]
+pub struct DumpArgs {
+    /// Alias name to dump
+    pub alias: String,
+
+    /// Output file (or directory for --dump-format directory); defaults to stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// PostgreSQL dump format (plain, custom, directory, or tar)
+    #[arg(long)]
+    pub dump_format: Option<DumpFormat>,
+}
+
+/// Logs command arguments
+#[derive(Debug, Args)]
+pub struct LogsArgs {
+    /// Alias whose container logs are shown
+    pub alias: String,
+
+    /// Number of lines to show from the end of the logs
+    #[arg(short = 'n', long, default_value_t = 100)]
+    pub tail: usize,
+
+    /// Keep printing new log output until interrupted
+    #[arg(short, long)]
+    pub follow: bool,
+}
+
+/// Uri command arguments
+#[derive(Debug, Args)]
+pub struct UriArgs {
+    /// Alias whose connection URI is printed
+    pub alias: String,
+
+    /// Host to put in the URI, e.g. where the container's port is published
+    #[arg(long, default_value = "localhost")]
+    pub host: String,
+
+    /// Port to put in the URI instead of the container-side port, e.g. the published one
+    #[arg(long)]
+    pub port: Option<u16>,
+
+    /// Include the password instead of `****`
+    #[arg(long)]
+    pub reveal: bool,
+}
+
+/// Completion command arguments
+#[derive(Debug, Args)]
+pub struct CompletionArgs {
+    /// Target shell
+    #[arg(value_enum)]
+    pub shell: CompletionShell,
+
+    /// Also complete saved aliases for connect, remove, and other alias commands
+    /// (bash, zsh, and fish only)
+    #[arg(long)]
+    pub aliases: bool,
 }
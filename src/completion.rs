@@ -0,0 +1,97 @@
+use clap::{CommandFactory, ValueEnum};
+use clap_complete::Shell;
+
+use crate::cli::Cli;
+
+/// Subcommands whose first positional argument is a saved alias
+const ALIAS_SUBCOMMANDS: &[&str] = &[
+    "connect",
+    "remove",
+    "show",
+    "dump",
+    "favorite",
+    "edit",
+    "rename",
+    "duplicate",
+    "exec",
+    "test",
+    "logs",
+    "uri",
+    "default",
+];
+
+/// Shells supported by the `completion` command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionShell {
+    /// Bourne Again shell
+    Bash,
+    /// Z shell
+    Zsh,
+    /// fish shell
+    Fish,
+    /// PowerShell
+    Powershell,
+}
+
+impl From<CompletionShell> for Shell {
+    fn from(shell: CompletionShell) -> Self {
+        match shell {
+            CompletionShell::Bash => Shell::Bash,
+            CompletionShell::Zsh => Shell::Zsh,
+            CompletionShell::Fish => Shell::Fish,
+            CompletionShell::Powershell => Shell::PowerShell,
+        }
+    }
+}
+
+/// Generate the static completion script for the CLI
+pub fn static_script(shell: CompletionShell) -> String {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    let mut buf = Vec::new();
+    clap_complete::generate(Shell::from(shell), &mut command, name, &mut buf);
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+/// Generate a snippet completing saved aliases via `ddcl __complete-aliases`
+///
+/// The snippet is meant to be sourced after the static script. PowerShell is not supported
+/// and returns `None`.
+pub fn alias_script(shell: CompletionShell) -> Option<String> {
+    let script = match shell {
+        CompletionShell::Bash => format!(
+            r#"
+_ddcl_with_aliases() {{
+    if [[ ${{COMP_CWORD}} -eq 2 && " {} " == *" ${{COMP_WORDS[1]}} "* ]]; then
+        COMPREPLY=($(compgen -W "$(ddcl __complete-aliases 2>/dev/null)" -- "${{COMP_WORDS[COMP_CWORD]}}"))
+    else
+        _ddcl "$@"
+    fi
+}}
+complete -F _ddcl_with_aliases -o bashdefault -o default ddcl
+"#,
+            ALIAS_SUBCOMMANDS.join(" ")
+        ),
+        CompletionShell::Zsh => format!(
+            r#"
+_ddcl_with_aliases() {{
+    if (( CURRENT == 3 )) && [[ ${{words[2]}} == ({}) ]]; then
+        local -a aliases
+        aliases=(${{(f)"$(ddcl __complete-aliases 2>/dev/null)"}})
+        _describe 'alias' aliases
+    else
+        _ddcl "$@"
+    fi
+}}
+compdef _ddcl_with_aliases ddcl
+"#,
+            ALIAS_SUBCOMMANDS.join("|")
+        ),
+        CompletionShell::Fish => format!(
+            "\ncomplete -c ddcl -n '__fish_seen_subcommand_from {}' -f -a '(ddcl __complete-aliases 2>/dev/null)'\n",
+            ALIAS_SUBCOMMANDS.join(" ")
+        ),
+        CompletionShell::Powershell => return None,
+    };
+    Some(script)
+}
@@ -0,0 +1,113 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, rand_core::RngCore};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use sha2::Sha256;
+
+use crate::error::{AppError, Result};
+
+/// Environment variable holding the master password for an encrypted config
+pub const MASTER_KEY_ENV: &str = "DDCL_MASTER_KEY";
+
+/// Header identifying an encrypted config file (never valid YAML content on its own)
+const MAGIC: &[u8] = b"DDCLENC1";
+/// PBKDF2-HMAC-SHA256 rounds used for new files; stored in the header so it can change later
+#[cfg(not(test))]
+const PBKDF2_ROUNDS: u32 = 600_000;
+/// Unoptimized test builds would spend seconds per key derivation
+#[cfg(test)]
+const PBKDF2_ROUNDS: u32 = 1_000;
+/// Round counts accepted from a file header; anything else is corrupt and could stall `load`
+const PBKDF2_ROUNDS_RANGE: std::ops::RangeInclusive<u32> = 1_000..=10_000_000;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + 4 + SALT_LEN + NONCE_LEN;
+
+/// Master password for an encrypted config; `Debug` never prints the value
+#[derive(Clone)]
+pub struct MasterKey(String);
+
+impl MasterKey {
+    /// Wrap a master password
+    pub fn new(password: String) -> Self {
+        Self(password)
+    }
+
+    /// The master password itself
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for MasterKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MasterKey(****)")
+    }
+}
+
+/// Master password from `DDCL_MASTER_KEY`, if set and non-empty
+pub fn master_key_from_env() -> Option<MasterKey> {
+    std::env::var(MASTER_KEY_ENV)
+        .ok()
+        .filter(|key| !key.is_empty())
+        .map(MasterKey::new)
+}
+
+/// Whether `data` starts with the encrypted config header
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Encrypt `plaintext` with AES-256-GCM under a key derived from `password`
+///
+/// Layout: magic, PBKDF2 rounds (big endian u32), salt, nonce, ciphertext with tag.
+pub fn encrypt(plaintext: &[u8], password: &str) -> Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let cipher = Aes256Gcm::new(&derive_key(password, &salt, PBKDF2_ROUNDS));
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| AppError::Encryption("Failed to encrypt config".to_string()))?;
+
+    let mut data = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&PBKDF2_ROUNDS.to_be_bytes());
+    data.extend_from_slice(&salt);
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    Ok(data)
+}
+
+/// Decrypt data produced by [`encrypt`]; a wrong password is reported, never garbage returned
+pub fn decrypt(data: &[u8], password: &str) -> Result<Vec<u8>> {
+    if !is_encrypted(data) || data.len() < HEADER_LEN {
+        return Err(AppError::Encryption(
+            "Not an encrypted config file".to_string(),
+        ));
+    }
+
+    let (rounds, rest) = data[MAGIC.len()..].split_at(4);
+    let rounds = u32::from_be_bytes(rounds.try_into().expect("split at 4 bytes"));
+    if !PBKDF2_ROUNDS_RANGE.contains(&rounds) {
+        return Err(AppError::Encryption(format!(
+            "Corrupted config file: PBKDF2 rounds {} outside {}..={}",
+            rounds,
+            PBKDF2_ROUNDS_RANGE.start(),
+            PBKDF2_ROUNDS_RANGE.end()
+        )));
+    }
+    let (salt, rest) = rest.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let cipher = Aes256Gcm::new(&derive_key(password, salt, rounds));
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| AppError::Encryption("Wrong master key or corrupted config file".to_string()))
+}
+
+/// Derive the AES-256 key from the master password
+fn derive_key(password: &str, salt: &[u8], rounds: u32) -> Key<Aes256Gcm> {
+    let mut key = Key::<Aes256Gcm>::default();
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, rounds, &mut key);
+    key
+}
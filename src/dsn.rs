@@ -0,0 +1,112 @@
+use std::str::FromStr;
+
+use crate::error::AppError;
+
+/// Connection parameters parsed from a libpq-style `key=value` connection string
+///
+/// e.g. `host=orders-db dbname=orders user=app port=5432`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dsn {
+    /// Container name (`host`)
+    pub host: Option<String>,
+    /// Username (`user`)
+    pub user: Option<String>,
+    /// Password (`password`)
+    pub password: Option<String>,
+    /// Database name (`dbname`)
+    pub database: Option<String>,
+    /// Port number (`port`)
+    pub port: Option<u16>,
+}
+
+impl FromStr for Dsn {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut dsn = Dsn::default();
+
+        for (key, value) in parse_pairs(s)? {
+            match key.as_str() {
+                "host" => dsn.host = Some(value),
+                "user" => dsn.user = Some(value),
+                "password" => dsn.password = Some(value),
+                "dbname" => dsn.database = Some(value),
+                "port" => {
+                    dsn.port = Some(value.parse().map_err(|_| {
+                        AppError::ValidationError(format!("Invalid port in DSN: '{}'", value))
+                    })?)
+                }
+                _ => eprintln!("Warning: Ignoring unknown DSN key '{}'", key),
+            }
+        }
+
+        Ok(dsn)
+    }
+}
+
+/// Split a connection string into key/value pairs
+///
+/// Follows libpq rules: whitespace around `=` is allowed, and values may be
+/// single-quoted with `\'` and `\\` escapes.
+fn parse_pairs(s: &str) -> Result<Vec<(String, String)>, AppError> {
+    let mut pairs = Vec::new();
+    let mut chars = s.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=') {
+            key.push(c);
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next() != Some('=') {
+            return Err(AppError::ValidationError(format!(
+                "Missing '=' after DSN key '{}'",
+                key
+            )));
+        }
+        if key.is_empty() {
+            return Err(AppError::ValidationError("Empty key in DSN".to_string()));
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let mut value = String::new();
+        if chars.next_if_eq(&'\'').is_some() {
+            loop {
+                match chars.next() {
+                    Some('\'') => break,
+                    Some('\\') => {
+                        if let Some(c) = chars.next() {
+                            value.push(c);
+                        }
+                    }
+                    Some(c) => value.push(c),
+                    None => {
+                        return Err(AppError::ValidationError(format!(
+                            "Unterminated quoted value for DSN key '{}'",
+                            key
+                        )));
+                    }
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                if c == '\\' {
+                    if let Some(escaped) = chars.next() {
+                        value.push(escaped);
+                    }
+                } else {
+                    value.push(c);
+                }
+            }
+        }
+
+        pairs.push((key, value));
+    }
+
+    Ok(pairs)
+}
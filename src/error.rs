@@ -15,6 +15,10 @@ pub enum AppError {
     #[error("Docker error: {0}")]
     Docker(String),
 
+    /// The container to connect to is not running (or does not exist)
+    #[error("Container '{0}' is not running; check the name with `ddcl detect` or `docker ps -a`")]
+    ContainerNotFound(String),
+
     /// Unknown database type errors
     #[error("Unknown database type: {0}")]
     UnknownDatabaseType(String),
@@ -23,6 +27,10 @@ pub enum AppError {
     #[error("Alias '{0}' not found")]
     AliasNotFound(String),
 
+    /// Alias already exists errors
+    #[error("Alias '{0}' already exists")]
+    AliasExists(String),
+
     /// I/O errors
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
@@ -31,6 +39,26 @@ pub enum AppError {
     #[error("YAML error: {0}")]
     Yaml(#[from] serde_yaml::Error),
 
+    /// JSON serialization/deserialization errors
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// OS keychain errors
+    #[error("Keychain error: {0}")]
+    Keyring(#[from] keyring::Error),
+
+    /// Docker did not respond in time
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    /// System clipboard errors
+    #[error("Clipboard error: {0}")]
+    Clipboard(#[from] arboard::Error),
+
+    /// Config encryption errors, including a wrong master key
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+
     /// Validation errors
     #[error("Validation error: {0}")]
     ValidationError(String),
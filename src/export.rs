@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::config::{Config, DatabaseConnection};
+use crate::db::{DatabaseConnector, DetectedContainer};
+use crate::error::{AppError, Result};
+use crate::validation::sanitize_for_shell;
+
+/// Stand-in password replaced by an environment variable reference in the script
+const PASSWORD_PLACEHOLDER: &str = "__DDCL_PASSWORD__";
+/// Stand-in container name replaced by a label lookup in the script
+const CONTAINER_PLACEHOLDER: &str = "ddcl-label-container";
+
+/// One row of `list --format json`; deliberately has no password field
+#[derive(Debug, Serialize)]
+pub struct ListEntry<'a> {
+    /// Alias name
+    pub alias: &'a str,
+    /// Database type
+    pub db_type: String,
+    /// Container name, or `label:<selector>`
+    pub container: String,
+    /// Username
+    pub user: &'a str,
+    /// Database name
+    pub database: Option<&'a str>,
+    /// `running` or `stopped`
+    pub status: &'static str,
+    /// Docker's status string (e.g. `Up 3 hours`), `None` if the container does not exist
+    pub docker_status: Option<&'a str>,
+    /// Tags, empty when the connection has none
+    pub tags: &'a [String],
+}
+
+impl<'a> ListEntry<'a> {
+    /// Build a row from a saved connection and its container's docker status
+    pub fn new(
+        alias: &'a str,
+        connection: &'a DatabaseConnection,
+        docker_status: Option<&'a str>,
+    ) -> Self {
+        let running = docker_status.is_some_and(DatabaseConnector::is_running_status);
+        Self {
+            alias,
+            db_type: connection.db_type.to_string(),
+            container: display_container(connection),
+            user: &connection.user,
+            database: connection.database.as_deref(),
+            status: if running { "running" } else { "stopped" },
+            docker_status,
+            tags: connection.tags.as_deref().unwrap_or_default(),
+        }
+    }
+}
+
+/// Tags shown after a listing row, e.g. ` #prod #orders`, or nothing without tags
+pub fn display_tags(connection: &DatabaseConnection) -> String {
+    connection
+        .tags
+        .iter()
+        .flatten()
+        .map(|tag| format!(" #{}", tag))
+        .collect()
+}
+
+/// Status shown in listings, e.g. `Running: Up 3 hours` or `Stopped: not found`
+pub fn display_status(docker_status: Option<&str>) -> String {
+    match docker_status {
+        Some(status) if DatabaseConnector::is_running_status(status) => {
+            format!("Running: {}", status)
+        }
+        Some(status) => format!("Stopped: {}", status),
+        None => "Stopped: not found".to_string(),
+    }
+}
+
+/// Container shown in listings: the name, or `label:<selector>` for label-based connections
+pub fn display_container(connection: &DatabaseConnection) -> String {
+    match &connection.container_label {
+        Some(label) => format!("label:{}", label),
+        None => connection.container.clone(),
+    }
+}
+
+/// Render `list` rows as a pretty-printed JSON array
+pub fn list_to_json(entries: &[ListEntry]) -> Result<String> {
+    let mut json = serde_json::to_string_pretty(entries)?;
+    json.push('\n');
+    Ok(json)
+}
+
+/// Output of `show --format json`: the alias followed by the connection's fields
+#[derive(Debug, Serialize)]
+struct ShowEntry<'a> {
+    alias: &'a str,
+    #[serde(flatten)]
+    connection: &'a DatabaseConnection,
+}
+
+/// Render a single connection as a pretty-printed JSON object
+pub fn show_to_json(alias: &str, connection: &DatabaseConnection) -> Result<String> {
+    let mut json = serde_json::to_string_pretty(&ShowEntry { alias, connection })?;
+    json.push('\n');
+    Ok(json)
+}
+
+/// Render `detect` results as a pretty-printed JSON array
+pub fn detected_to_json(containers: &[DetectedContainer]) -> Result<String> {
+    let mut json = serde_json::to_string_pretty(containers)?;
+    json.push('\n');
+    Ok(json)
+}
+
+/// Export the whole config as pretty-printed JSON
+pub fn to_json(config: &Config) -> Result<String> {
+    let mut json = serde_json::to_string_pretty(config)?;
+    json.push('\n');
+    Ok(json)
+}
+
+/// Export connections as a standalone shell script with one function per alias
+pub fn to_shell_script(config: &Config) -> Result<String> {
+    let mut script = String::from(
+        "#!/bin/sh\n# Generated by ddcl. Source this file to use the functions below.\n",
+    );
+    // Aliases differing only in punctuation or case would define the same function or
+    // password variable, and the later definition would silently win
+    let mut identifiers: HashMap<String, &str> = HashMap::new();
+    for (alias, connection) in config.iter_sorted() {
+        let identifier = shell_identifier(alias);
+        if let Some(other) = identifiers.insert(identifier.to_uppercase(), alias) {
+            return Err(AppError::Config(format!(
+                "Aliases '{}' and '{}' both export as shell function 'ddcl_{}'; rename one of them",
+                other, alias, identifier
+            )));
+        }
+        script.push('\n');
+        script.push_str(&shell_function(alias, connection)?);
+    }
+
+    Ok(script)
+}
+
+/// Build a shell function running the raw `docker exec` command for a connection
+fn shell_function(alias: &str, connection: &DatabaseConnection) -> Result<String> {
+    let identifier = shell_identifier(alias);
+    let password_var = format!("DDCL_{}_PASSWORD", identifier.to_uppercase());
+
+    // Build the arguments with placeholders so secrets and generated names stay out of the script
+    let mut exported = connection.clone();
+    if exported.password.is_some() {
+        exported.password = Some(PASSWORD_PLACEHOLDER.to_string());
+    }
+    let docker_host = DatabaseConnector::docker_host_prefix(connection);
+    let container_lookup = exported.container_label.take().map(|label| {
+        format!(
+            "\"$({}docker ps --filter {} --format '{{{{.Names}}}}')\"",
+            docker_host,
+            sanitize_for_shell(&format!("label={}", label))
+        )
+    });
+    if container_lookup.is_some() {
+        exported.container = CONTAINER_PLACEHOLDER.to_string();
+    }
+
+    let args = DatabaseConnector::build_args(&exported)?;
+    let rendered: Vec<String> = args
+        .iter()
+        .map(|arg| match &container_lookup {
+            Some(lookup) if arg == CONTAINER_PLACEHOLDER => lookup.clone(),
+            _ if arg.contains(PASSWORD_PLACEHOLDER) => format!(
+                "\"{}\"",
+                escape_double_quoted(arg)
+                    .replace(PASSWORD_PLACEHOLDER, &format!("${{{}}}", password_var))
+            ),
+            _ => sanitize_for_shell(arg),
+        })
+        .collect();
+
+    let mut function = format!("# {}: {}\n", alias, connection.db_type);
+    if connection.password.is_some() {
+        function.push_str(&format!("# Requires {} to be set\n", password_var));
+    }
+    function.push_str(&format!(
+        "ddcl_{}() {{\n    {}docker {} \"$@\"\n}}\n",
+        identifier,
+        docker_host,
+        rendered.join(" ")
+    ));
+
+    Ok(function)
+}
+
+/// Convert an alias into a valid shell identifier
+fn shell_identifier(alias: &str) -> String {
+    alias
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Escape characters that are special inside double quotes
+fn escape_double_quoted(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | '$' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -0,0 +1,112 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+/// Maximum number of aliases kept in the history file
+const MAX_ENTRIES: usize = 100;
+
+/// A successful connection to a saved alias
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// Alias name
+    pub alias: String,
+    /// Time of the connection (seconds since the Unix epoch)
+    pub timestamp: u64,
+}
+
+/// Connection history stored next to the config file, most recent first
+///
+/// `config.yaml` keeps its history in `history.yaml` and a profile's `config.<name>.yaml` in
+/// `history.<name>.yaml`, so profiles never list each other's aliases.
+/// Each alias appears once, at the time it was last used.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct History {
+    /// Entries ordered from most to least recently used
+    #[serde(default)]
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    /// Get the path of the history file belonging to the config file at `config_path`
+    pub fn get_history_path(config_path: &Path) -> PathBuf {
+        let file_name = config_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        // `config.yaml` -> `.yaml`, `config.work.yaml` -> `.work.yaml`
+        let suffix = file_name.strip_prefix("config").unwrap_or(".yaml");
+        config_path.with_file_name(format!("history{}", suffix))
+    }
+
+    /// Load the history from `path`, returning an empty history if the file does not exist
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let history_str = fs::read_to_string(path)?;
+        Ok(serde_yaml::from_str(&history_str)?)
+    }
+
+    /// Save the history to `path`
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Move `alias` to the front of the history with the given timestamp
+    pub fn record(&mut self, alias: &str, timestamp: u64) {
+        self.entries.retain(|entry| entry.alias != alias);
+        self.entries.insert(
+            0,
+            HistoryEntry {
+                alias: alias.to_string(),
+                timestamp,
+            },
+        );
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    /// Most recently used entries, up to `limit` if given
+    pub fn recent(&self, limit: Option<usize>) -> &[HistoryEntry] {
+        let len = limit.map_or(self.entries.len(), |limit| limit.min(self.entries.len()));
+        &self.entries[..len]
+    }
+
+    /// Record a connection to `alias` now in the history of the config file at `config_path`
+    pub fn record_connection(config_path: &Path, alias: &str) -> Result<()> {
+        let path = Self::get_history_path(config_path);
+        let mut history = Self::load_from(&path)?;
+        history.record(alias, now());
+        history.save_to(&path)
+    }
+}
+
+/// Current time in seconds since the Unix epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Describe how long ago `timestamp` was, e.g. `5 minutes ago`
+pub fn format_elapsed(timestamp: u64, now: u64) -> String {
+    let elapsed = now.saturating_sub(timestamp);
+    let (value, unit) = match elapsed {
+        0..60 => return "just now".to_string(),
+        60..3600 => (elapsed / 60, "minute"),
+        3600..86400 => (elapsed / 3600, "hour"),
+        _ => (elapsed / 86400, "day"),
+    };
+
+    format!(
+        "{} {}{} ago",
+        value,
+        unit,
+        if value == 1 { "" } else { "s" }
+    )
+}
@@ -1,7 +1,10 @@
-use crate::config::{DatabaseConnection, DatabaseType};
+use crate::config::{Config, DatabaseConnection, DatabaseType};
+use crate::crypto::MasterKey;
 use crate::db::DatabaseConnector;
 use crate::error::{AppError, Result};
-use dialoguer::{Input, Password, Select, theme::ColorfulTheme};
+use crate::export::display_container;
+use crate::validation::warn_port_for_type;
+use dialoguer::{Confirm, Input, Password, Select, theme::ColorfulTheme};
 use std::str::FromStr;
 
 // Implementation of From trait to convert dialoguer::Error
@@ -20,31 +23,69 @@ pub async fn get_connection_interactively() -> Result<(String, DatabaseConnectio
         .with_prompt("alias name")
         .interact()?;
 
+    let connection = prompt_connection(&theme).await?;
+
+    Ok((alias, connection))
+}
+
+/// Get connection information interactively without asking for an alias
+pub async fn get_unsaved_connection_interactively() -> Result<DatabaseConnection> {
+    prompt_connection(&ColorfulTheme::default()).await
+}
+
+/// Prompt for everything but the alias
+async fn prompt_connection(theme: &ColorfulTheme) -> Result<DatabaseConnection> {
     // Input container name (with auto-detection option)
-    let container = get_container_interactively(&theme).await?;
+    let container = get_container_interactively(theme).await?;
 
     // Select database type
-    let db_types = &["PostgreSQL", "MySQL", "MongoDB"];
-    let db_type_index = Select::with_theme(&theme)
+    let db_types = &[
+        "PostgreSQL",
+        "MySQL",
+        "MongoDB",
+        "SQLite",
+        "CockroachDB",
+        "OpenSearch",
+    ];
+    let db_type_index = Select::with_theme(theme)
         .with_prompt("Database type")
         .items(db_types)
         .default(0)
         .interact()?;
     let db_type = DatabaseType::from_str(db_types[db_type_index])?;
 
+    // SQLite has no user, password, or port: only the file path is needed
+    if db_type == DatabaseType::SQLite {
+        let path: String = Input::with_theme(theme)
+            .with_prompt("Database file path in the container")
+            .interact()?;
+        let exec_user = prompt_exec_user(theme, None)?;
+        return Ok(DatabaseConnection {
+            db_type,
+            container,
+            database: Some(path),
+            exec_user,
+            ..Default::default()
+        });
+    }
+
     // Input username (set default value according to database type)
     let default_user = match db_type {
         DatabaseType::PostgreSQL => "postgres",
         DatabaseType::MySQL => "root",
         DatabaseType::MongoDB => "mongo",
+        DatabaseType::CockroachDB => "root",
+        // Clusters with the security plugin disabled take no credentials
+        DatabaseType::OpenSearch | DatabaseType::SQLite => "",
     };
-    let user: String = Input::with_theme(&theme)
-        .with_prompt("DB username")
+    let user: String = Input::with_theme(theme)
+        .with_prompt(user_prompt(&db_type))
         .default(default_user.to_string())
+        .allow_empty(!db_type.requires_user())
         .interact()?;
 
     // Input password (optional)
-    let password: String = Password::with_theme(&theme)
+    let password: String = Password::with_theme(theme)
         .with_prompt("Password (Optional)")
         .allow_empty_password(true)
         .interact()?;
@@ -55,18 +96,24 @@ pub async fn get_connection_interactively() -> Result<(String, DatabaseConnectio
     };
 
     // Input database name (optional)
-    let database: String = Input::with_theme(&theme)
-        .with_prompt("Database name(Optional)")
-        .allow_empty(true)
-        .interact()?;
-    let database = if database.is_empty() {
+    let database = if db_type.has_databases() {
+        let database: String = Input::with_theme(theme)
+            .with_prompt("Database name(Optional)")
+            .allow_empty(true)
+            .interact()?;
+        (!database.is_empty()).then_some(database)
+    } else {
         None
+    };
+
+    let auth_database = if db_type == DatabaseType::MongoDB && !user.is_empty() {
+        prompt_auth_database(theme, None)?
     } else {
-        Some(database)
+        None
     };
 
     // Input port number (optional)
-    let port_str: String = Input::with_theme(&theme)
+    let port_str: String = Input::with_theme(theme)
         .with_prompt("Port number (Optional)")
         .allow_empty(true)
         .interact()?;
@@ -81,6 +128,9 @@ pub async fn get_connection_interactively() -> Result<(String, DatabaseConnectio
             }
         }
     };
+    warn_port_for_type(port, &db_type);
+
+    let exec_user = prompt_exec_user(theme, None)?;
 
     // Create connection information
     let connection = DatabaseConnection {
@@ -91,9 +141,171 @@ pub async fn get_connection_interactively() -> Result<(String, DatabaseConnectio
         database,
         port,
         options: None,
+        auth_database,
+        exec_user,
+        ..Default::default()
     };
 
-    Ok((alias, connection))
+    Ok(connection)
+}
+
+/// Username prompt, marked optional for types that can connect without credentials
+fn user_prompt(db_type: &DatabaseType) -> &'static str {
+    if db_type.requires_user() {
+        "DB username"
+    } else {
+        "DB username (Optional)"
+    }
+}
+
+/// Ask for the MongoDB authentication database, pre-filled with `default`
+fn prompt_auth_database(theme: &ColorfulTheme, default: Option<&str>) -> Result<Option<String>> {
+    let auth_database: String = Input::with_theme(theme)
+        .with_prompt("Authentication database (Optional, e.g. admin)")
+        .default(default.unwrap_or_default().to_string())
+        .allow_empty(true)
+        .interact_text()?;
+    Ok((!auth_database.is_empty()).then_some(auth_database))
+}
+
+/// Ask for the OS user `docker exec` runs the client as, pre-filled with `default`
+fn prompt_exec_user(theme: &ColorfulTheme, default: Option<&str>) -> Result<Option<String>> {
+    let exec_user: String = Input::with_theme(theme)
+        .with_prompt("Container OS user to exec as (Optional, not the DB user)")
+        .default(default.unwrap_or_default().to_string())
+        .allow_empty(true)
+        .interact_text()?;
+    Ok((!exec_user.is_empty()).then_some(exec_user))
+}
+
+/// Edit a connection interactively, pre-filling each prompt with the current value
+pub fn edit_connection_interactively(
+    connection: &DatabaseConnection,
+) -> Result<DatabaseConnection> {
+    let theme = ColorfulTheme::default();
+    let mut edited = connection.clone();
+
+    edited.container = Input::with_theme(&theme)
+        .with_prompt("Container name")
+        .default(connection.container.clone())
+        .interact_text()?;
+
+    if connection.db_type.supports_credentials() {
+        edited.user = Input::with_theme(&theme)
+            .with_prompt(user_prompt(&connection.db_type))
+            .default(connection.user.clone())
+            .allow_empty(!connection.db_type.requires_user())
+            .interact_text()?;
+
+        // Passwords are never echoed, so an empty answer keeps the current one
+        let password: String = Password::with_theme(&theme)
+            .with_prompt("Password (leave empty to keep current)")
+            .allow_empty_password(true)
+            .interact()?;
+        if !password.is_empty() {
+            edited.password = Some(password);
+        }
+    }
+
+    if connection.db_type.has_databases() {
+        let database: String = Input::with_theme(&theme)
+            .with_prompt("Database name(Optional)")
+            .default(connection.database.clone().unwrap_or_default())
+            .allow_empty(true)
+            .interact_text()?;
+        edited.database = (!database.is_empty()).then_some(database);
+    }
+
+    if edited.db_type == DatabaseType::MongoDB && !edited.user.is_empty() {
+        edited.auth_database = prompt_auth_database(&theme, connection.auth_database.as_deref())?;
+    }
+
+    if connection.db_type.default_port().is_some() {
+        let port: String = Input::with_theme(&theme)
+            .with_prompt("Port number (Optional)")
+            .default(connection.port.map(|p| p.to_string()).unwrap_or_default())
+            .allow_empty(true)
+            .interact_text()?;
+        edited.port = if port.is_empty() {
+            None
+        } else {
+            match port.parse::<u16>() {
+                Ok(p) => Some(p),
+                Err(_) => {
+                    println!("Warning: Invalid port number provided, keeping current port.");
+                    connection.port
+                }
+            }
+        };
+        warn_port_for_type(edited.port, &edited.db_type);
+    }
+
+    edited.exec_user = prompt_exec_user(&theme, connection.exec_user.as_deref())?;
+
+    Ok(edited)
+}
+
+/// Ask whether to save a connection after `connect --interactive`, returning the alias if so
+pub fn prompt_save_alias() -> Result<Option<String>> {
+    let theme = ColorfulTheme::default();
+
+    let save = Confirm::with_theme(&theme)
+        .with_prompt("Save this connection as an alias?")
+        .default(false)
+        .interact()?;
+    if !save {
+        return Ok(None);
+    }
+
+    let alias: String = Input::with_theme(&theme)
+        .with_prompt("alias name")
+        .interact()?;
+    Ok(Some(alias))
+}
+
+/// Offer to save a connection made with `connect --interactive`
+///
+/// `prompt` is skipped when `no_save` is set; otherwise the connection is saved under the
+/// alias it returns. An existing alias is only replaced after `confirm` accepts the change
+/// preview and, for a protected connection, `confirm_protected` returns the alias, the same
+/// checks `add --overwrite` and `remove` apply. Returns the alias the connection was saved as.
+pub fn save_after_connect<F, C, P>(
+    config: &mut Config,
+    connection: DatabaseConnection,
+    no_save: bool,
+    prompt: F,
+    confirm: C,
+    confirm_protected: P,
+) -> Result<Option<String>>
+where
+    F: FnOnce() -> Result<Option<String>>,
+    C: FnOnce(&str) -> Result<bool>,
+    P: FnOnce(&str) -> Result<String>,
+{
+    if no_save {
+        return Ok(None);
+    }
+
+    let Some(alias) = prompt()? else {
+        return Ok(None);
+    };
+    let seen = config.connections.get(&alias).cloned();
+    if let Some(existing) = &seen {
+        existing.guard_protected(&alias, false, || confirm_protected(&alias))?;
+        // The session was interactive, so there is always a terminal to ask on
+        if !confirm_overwrite(existing, &connection, true, confirm)? {
+            return Ok(None);
+        }
+    }
+
+    if let Some(warning) = config.plaintext_password_warning(&alias, &connection)? {
+        eprintln!("Warning: {}", warning);
+    }
+    config.with_locked_config(|config| {
+        config.ensure_unchanged(&alias, seen.as_ref())?;
+        config.add_connection(alias.clone(), connection)
+    })?;
+    Ok(Some(alias))
 }
 
 /// Select or input container interactively
@@ -135,12 +347,123 @@ async fn get_container_interactively(theme: &ColorfulTheme) -> Result<String> {
     }
 }
 
+/// Let the user pick one of `items`, returning its index (`None` if cancelled with Esc)
+pub fn select_connection(items: &[String]) -> Result<Option<usize>> {
+    Ok(Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Select a connection")
+        .items(items)
+        .default(0)
+        .interact_opt()?)
+}
+
+/// Ask for the config master password, twice when `confirm` is set
+pub fn prompt_master_key(confirm: bool) -> Result<MasterKey> {
+    let theme = ColorfulTheme::default();
+    let mut prompt = Password::with_theme(&theme).with_prompt("Master password");
+    if confirm {
+        prompt = prompt.with_confirmation("Repeat master password", "Passwords do not match");
+    }
+
+    Ok(MasterKey::new(prompt.interact()?))
+}
+
+/// Ask the user to type the alias of a protected connection to confirm
+pub fn confirm_protected_alias(alias: &str) -> Result<String> {
+    let confirmation: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!(
+            "Connection '{}' is protected. Type its alias to confirm",
+            alias
+        ))
+        .allow_empty(true)
+        .interact_text()?;
+
+    Ok(confirmation)
+}
+
+/// Ask a yes/no question, defaulting to no
+pub fn prompt_confirm(prompt: &str) -> Result<bool> {
+    Ok(Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .default(false)
+        .interact()?)
+}
+
+/// Decide whether `add --overwrite` may replace an existing connection
+///
+/// On a terminal the changed fields are passed to `confirm` for the user to approve;
+/// otherwise the overwrite proceeds without prompting so scripts keep working.
+pub fn confirm_overwrite<F>(
+    existing: &DatabaseConnection,
+    new: &DatabaseConnection,
+    is_terminal: bool,
+    confirm: F,
+) -> Result<bool>
+where
+    F: FnOnce(&str) -> Result<bool>,
+{
+    if !is_terminal {
+        return Ok(true);
+    }
+
+    let mut preview = String::new();
+    for (field, old, new) in existing.masked().diff(&new.masked()) {
+        preview.push_str(&format!(
+            "  {}: {} -> {}\n",
+            field,
+            old.as_deref().unwrap_or("-"),
+            new.as_deref().unwrap_or("-")
+        ));
+    }
+    if preview.is_empty() {
+        preview.push_str("  (no changes)\n");
+    }
+
+    confirm(&preview)
+}
+
+/// Decide whether `remove` may delete a connection
+///
+/// `yes` (`--yes`) skips the question. Otherwise `confirm` is asked with a prompt naming the
+/// database type and container; without a terminal to ask on, the removal is refused.
+pub fn confirm_remove<F>(
+    alias: &str,
+    connection: &DatabaseConnection,
+    yes: bool,
+    is_terminal: bool,
+    confirm: F,
+) -> Result<bool>
+where
+    F: FnOnce(&str) -> Result<bool>,
+{
+    if yes {
+        return Ok(true);
+    }
+    if !is_terminal {
+        return Err(AppError::ValidationError(format!(
+            "Refusing to remove '{}' without confirmation; pass --yes to skip the prompt",
+            alias
+        )));
+    }
+
+    confirm(&format!(
+        "Remove connection '{}' ({} in {})?",
+        alias,
+        connection.db_type,
+        display_container(connection)
+    ))
+}
+
 /// Get connection information interactively (with auto-detection)
-pub async fn get_connection_with_auto_detect() -> Result<(String, DatabaseConnection)> {
+///
+/// With `db_type`, only containers of that database type are offered.
+pub async fn get_connection_with_auto_detect(
+    db_type: Option<DatabaseType>,
+) -> Result<(String, DatabaseConnection)> {
     let theme = ColorfulTheme::default();
 
     // Detect running database containers
-    let detected_containers = DatabaseConnector::detect_database_containers().await?;
+    let detected_containers =
+        DatabaseConnector::detect_database_containers_filtered(db_type, None).await?;
 
     if detected_containers.is_empty() {
         println!("No database containers detected.");
@@ -190,10 +513,13 @@ pub async fn get_connection_with_auto_detect() -> Result<(String, DatabaseConnec
                 DatabaseType::PostgreSQL => "postgres".to_string(),
                 DatabaseType::MySQL => "root".to_string(),
                 DatabaseType::MongoDB => "mongo".to_string(),
+                DatabaseType::CockroachDB => "root".to_string(),
+                DatabaseType::OpenSearch | DatabaseType::SQLite => String::new(),
             });
     let user: String = Input::with_theme(&theme)
-        .with_prompt("DB username")
+        .with_prompt(user_prompt(&selected_container.db_type))
         .default(default_user)
+        .allow_empty(!selected_container.db_type.requires_user())
         .interact()?;
 
     // Input password
@@ -231,16 +557,24 @@ pub async fn get_connection_with_auto_detect() -> Result<(String, DatabaseConnec
     };
 
     // Input database name
-    let default_database = defaults.get("database").cloned().unwrap_or_default();
-    let database: String = Input::with_theme(&theme)
-        .with_prompt("Database name (Optional)")
-        .default(default_database)
-        .allow_empty(true)
-        .interact()?;
-    let database = if database.is_empty() {
+    let database = if selected_container.db_type.has_databases() {
+        let default_database = defaults.get("database").cloned().unwrap_or_default();
+        let database: String = Input::with_theme(&theme)
+            .with_prompt("Database name (Optional)")
+            .default(default_database)
+            .allow_empty(true)
+            .interact()?;
+        (!database.is_empty()).then_some(database)
+    } else {
         None
+    };
+
+    // Users created from MONGO_INITDB_ROOT_USERNAME live in the admin database
+    let auth_database = if selected_container.db_type == DatabaseType::MongoDB && !user.is_empty() {
+        let default = defaults.contains_key("user").then_some("admin");
+        prompt_auth_database(&theme, default)?
     } else {
-        Some(database)
+        None
     };
 
     // Input port number
@@ -259,6 +593,9 @@ pub async fn get_connection_with_auto_detect() -> Result<(String, DatabaseConnec
             }
         }
     };
+    warn_port_for_type(port, &selected_container.db_type);
+
+    let exec_user = prompt_exec_user(&theme, None)?;
 
     // Create connection information
     let connection = DatabaseConnection {
@@ -269,6 +606,9 @@ pub async fn get_connection_with_auto_detect() -> Result<(String, DatabaseConnec
         database,
         port,
         options: None,
+        auth_database,
+        exec_user,
+        ..Default::default()
     };
 
     Ok((alias, connection))
@@ -0,0 +1,61 @@
+use std::io::Write;
+
+use chrono::{SecondsFormat, Utc};
+use log::kv::{self, VisitSource};
+use serde_json::{Map, Value};
+
+use crate::cli::LogFormat;
+
+/// Install the global logger: errors only, `info` with `--verbose`, or whatever `RUST_LOG` says
+pub fn init(verbose: bool, format: LogFormat) {
+    // RUST_LOG still takes precedence over --verbose
+    let default_level = if verbose { "info" } else { "error" };
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(default_level));
+    if format == LogFormat::Json {
+        builder.format(|buf, record| writeln!(buf, "{}", json_record(record)));
+    }
+    builder.init();
+}
+
+/// One log record as a JSON object, with its key-values under `fields`
+///
+/// e.g. `{"timestamp":"…","level":"INFO","target":"…","message":"…","fields":{"container":"pg"}}`
+pub fn json_record(record: &log::Record) -> Value {
+    let mut event = Map::new();
+    event.insert(
+        "timestamp".to_string(),
+        Value::from(Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)),
+    );
+    event.insert("level".to_string(), Value::from(record.level().as_str()));
+    event.insert("target".to_string(), Value::from(record.target()));
+    event.insert(
+        "message".to_string(),
+        Value::from(record.args().to_string()),
+    );
+
+    let mut fields = Fields::default();
+    // Collecting into a map cannot fail
+    let _ = record.key_values().visit(&mut fields);
+    if !fields.0.is_empty() {
+        event.insert("fields".to_string(), Value::Object(fields.0));
+    }
+    Value::Object(event)
+}
+
+/// Key-values of a record, keeping booleans and numbers typed
+#[derive(Default)]
+struct Fields(Map<String, Value>);
+
+impl<'kvs> VisitSource<'kvs> for Fields {
+    fn visit_pair(&mut self, key: kv::Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        let value = value
+            .to_bool()
+            .map(Value::from)
+            .or_else(|| value.to_u64().map(Value::from))
+            .or_else(|| value.to_i64().map(Value::from))
+            .unwrap_or_else(|| Value::from(value.to_string()));
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
@@ -1,11 +1,33 @@
 use anyhow::Context;
+use chrono::Utc;
 use clap::Parser;
 use docker_db_container_login::{
-    Config, DatabaseConnector, Result,
-    cli::{Commands, ConnectArgs},
+    AppError, Cli, Config, DatabaseConnection, DatabaseConnector, DatabaseType, PasswordSource,
+    Result,
+    cli::{
+        AddArgs, ClearArgs, Commands, CompletionArgs, ConfigCommands, ConnectArgs, DefaultArgs,
+        DetectArgs, DumpArgs, DuplicateArgs, EditArgs, ExecArgs, ExportArgs, ExportFormat,
+        ImportArgs, ListArgs, ListFormat, ListSort, LogFormat, PresetArgs, PresetCommands,
+        RecentArgs, RemoveArgs, RenameArgs, ShowArgs, ShowFormat, TestArgs, UriArgs,
+        parse_entrypoint, read_password_line,
+    },
+    completion, crypto, db, export,
+    history::{self, History},
+    logging, validation,
 };
-use docker_db_container_login::{get_connection_interactively, get_connection_with_auto_detect};
+use docker_db_container_login::{
+    confirm_overwrite, confirm_protected_alias, confirm_remove, edit_connection_interactively,
+    get_connection_interactively, get_connection_with_auto_detect,
+    get_unsaved_connection_interactively, prompt_confirm, prompt_master_key, prompt_save_alias,
+    save_after_connect, select_connection,
+};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::Path;
 use std::process;
+use std::time::Duration;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -16,95 +38,1060 @@ struct Args {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    env_logger::init();
+    let cli = Cli::parse();
+    logging::init(cli.verbose, cli.log_format);
 
-    let cli = docker_db_container_login::Cli::parse();
+    let log_format = cli.log_format;
+    let result = run_cli(cli).await;
+    // Text mode already prints the error below; log pipelines need it as an event
+    if log_format == LogFormat::Json
+        && let Err(e) = &result
+    {
+        log::error!(error:% = format!("{:#}", e); "Command failed");
+    }
+    result
+}
 
-    let mut config = Config::load().context("Failed to load config")?;
-    match cli.command {
-        Commands::Connect(args) => connect_command(args, &config).await?,
-        Commands::Add(args) => {
-            if args.auto_detect {
-                let (alias, connection) = get_connection_with_auto_detect()
-                    .await
-                    .context("Failed in auto-detect mode input")?;
+async fn run_cli(cli: Cli) -> anyhow::Result<()> {
+    let config_path = Config::get_config_path(cli.profile.as_deref())?;
 
-                config
-                    .add_connection(alias.clone(), connection)
-                    .context("Failed to add connection config")?;
+    // Runs before loading the config so that a broken one is diagnosed instead of fatal
+    if matches!(cli.command, Some(Commands::Doctor)) {
+        let healthy =
+            db::scope_timeout(Duration::from_secs(cli.timeout), doctor(&config_path)).await;
+        if !healthy {
+            process::exit(1);
+        }
+        return Ok(());
+    }
 
-                println!("Connection config '{}' added", alias);
-            } else if args.interactive {
-                let (alias, connection) = get_connection_interactively()
-                    .await
-                    .context("Failed in interactive mode input")?;
+    let master_key = match crypto::master_key_from_env() {
+        Some(key) => Some(key),
+        // Only ask twice when the file is about to be encrypted for the first time
+        None if cli.encrypted => Some(prompt_master_key(!Config::is_encrypted_at(&config_path)?)?),
+        None => None,
+    };
+    let mut config =
+        Config::load_from_with_key(&config_path, master_key).context("Failed to load config")?;
+    config.force_save(cli.force);
 
-                config
-                    .add_connection(alias.clone(), connection)
-                    .context("Failed to add connection config")?;
+    let no_validate = cli.no_validate;
+    let docker_host = cli
+        .command
+        .as_ref()
+        .and_then(|command| command_docker_host(command, &config));
+    let run = db::scope_docker_host(
+        docker_host,
+        db::scope_timeout(
+            Duration::from_secs(cli.timeout),
+            // The command futures are large enough to overflow the main thread's stack in debug builds
+            db::scope_container_cache(Box::pin(run(cli, config))),
+        ),
+    );
+    if no_validate {
+        eprintln!("Warning: Name validation is disabled (--no-validate)");
+        validation::scope_bypass(run).await
+    } else {
+        run.await
+    }
+}
 
-                println!("Connection config '{}' added", alias);
-            } else {
-                let alias = match &args.alias {
-                    Some(alias) => alias.clone(),
-                    None => {
-                        return Err(anyhow::anyhow!("Alias name not specified"));
-                    }
-                };
+async fn run(cli: Cli, mut config: Config) -> anyhow::Result<()> {
+    let Some(command) = cli.command else {
+        return launch(&mut config).await;
+    };
 
-                let connection = args
-                    .to_connection()
-                    .map_err(|e| anyhow::anyhow!("Failed to convert connection info: {}", e))?;
+    match command {
+        Commands::Connect(args) => connect_command(args, &mut config).await?,
+        Commands::Add(args) => add_command(args, &mut config).await?,
+        Commands::Edit(args) => edit_command(args, &mut config)?,
+        Commands::Rename(args) => rename_command(args, &mut config)?,
+        Commands::Duplicate(args) => duplicate_command(args, &mut config)?,
+        Commands::Remove(args) => remove_command(args, &mut config)?,
+        Commands::Clear(args) => clear_command(args, &mut config)?,
+        Commands::List(args) => list_command(args, &config).await?,
+        Commands::Detect(args) => detect_command(args).await?,
+        Commands::Recent(args) => recent_command(args, &config)?,
+        Commands::Favorite(args) => {
+            let favorite = config
+                .with_locked_config(|config| config.toggle_favorite(&args.alias))
+                .context("Failed to toggle favorite")?;
 
+            if favorite {
+                println!("Connection config '{}' marked as favorite", args.alias);
+            } else {
+                println!("Connection config '{}' unmarked as favorite", args.alias);
+            }
+        }
+        Commands::Default(args) => default_command(args, &mut config)?,
+        Commands::Preset(args) => preset_command(args, &mut config)?,
+        Commands::Dump(args) => dump_command(args, &config).await?,
+        Commands::Logs(args) => {
+            let connection = config.get_connection(&args.alias)?;
+            let container = DatabaseConnector::resolve_container(connection).await?;
+
+            DatabaseConnector::stream_logs(&container, args.tail, args.follow)
+                .await
+                .context("Failed to show container logs")?;
+        }
+        Commands::Uri(args) => uri_command(args, &mut config)?,
+        Commands::Exec(args) => exec_command(args, &config).await?,
+        Commands::Test(args) => test_command(args, &config).await?,
+        Commands::Show(args) => show_command(args, &config)?,
+        Commands::Export(args) => export_command(args, &config)?,
+        Commands::Import(args) => import_command(args, &mut config)?,
+        Commands::Profiles => profiles_command(cli.profile.as_deref())?,
+        Commands::Config(args) => match args.command {
+            ConfigCommands::Normalize => {
                 config
-                    .add_connection(alias.clone(), connection)
-                    .context("Failed to add connection config")?;
+                    .with_locked_config(|_| Ok(()))
+                    .context("Failed to save normalized config")?;
+                println!(
+                    "Config normalized: {}",
+                    Config::get_config_path(cli.profile.as_deref())?.display()
+                );
+            }
+        },
+        Commands::Completion(args) => completion_command(args)?,
+        Commands::CompleteAliases => {
+            for (alias, _) in config.iter_sorted() {
+                println!("{}", alias);
+            }
+        }
+        Commands::Validate => validate_command(&config)?,
+        Commands::Doctor => unreachable!("doctor runs before the config is loaded"),
+        Commands::CheckName(args) => match args.kind.validate(&args.value) {
+            Ok(()) => println!("OK"),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        },
+    }
+
+    Ok(())
+}
+
+/// Save a new connection, or replace one with `--overwrite` (`ddcl add`)
+async fn add_command(mut args: AddArgs, config: &mut Config) -> anyhow::Result<()> {
+    if args.password_stdin {
+        args.password = Some(read_password_line(io::stdin().lock())?);
+    }
 
-                println!("Connection config '{}' added", alias);
+    let (alias, mut connection) = if args.auto_detect {
+        // In auto-detect mode --db-type narrows the detected containers
+        let db_type = args
+            .db_type
+            .as_deref()
+            .map(str::parse::<DatabaseType>)
+            .transpose()?;
+        get_connection_with_auto_detect(db_type)
+            .await
+            .context("Failed in auto-detect mode input")?
+    } else if args.interactive {
+        get_connection_interactively()
+            .await
+            .context("Failed in interactive mode input")?
+    } else {
+        let alias = match &args.alias {
+            Some(alias) => alias.clone(),
+            None => {
+                return Err(anyhow::anyhow!("Alias name not specified"));
             }
+        };
+
+        let connection = args
+            .to_connection()
+            .map_err(|e| anyhow::anyhow!("Failed to convert connection info: {}", e))?;
+
+        (alias, connection)
+    };
+    validation::validate_alias(&alias)?;
+    if let Some(source) = args.password_source {
+        connection.password_source = Some(source);
+    }
+
+    if args.no_save {
+        let entry = HashMap::from([(alias, connection.masked())]);
+        print!(
+            "{}",
+            serde_yaml::to_string(&entry).context("Failed to serialize connection")?
+        );
+        return Ok(());
+    }
+
+    // The overwrite is confirmed against this version; it must still be current under the lock
+    let seen = config.connections.get(&alias).cloned();
+    if let Some(existing) = &seen {
+        if !args.overwrite {
+            return Err(anyhow::anyhow!(
+                "Connection config '{}' already exists (use --overwrite to replace it)",
+                alias
+            ));
         }
-        Commands::Remove(args) => {
-            config
-                .remove_connection(&args.alias)
-                .context("Failed to remove connection config")?;
 
-            println!("Connection config '{}' removed", args.alias);
+        let confirmed = confirm_overwrite(
+            existing,
+            &connection,
+            io::stdin().is_terminal(),
+            |preview| {
+                println!("Changes to '{}':\n{}", alias, preview);
+                prompt_confirm(&format!("Overwrite connection config '{}'?", alias))
+            },
+        )?;
+        if !confirmed {
+            println!("Aborted");
+            return Ok(());
         }
-        Commands::List => {
-            let connections = config.list_connections();
+    }
 
-            if connections.is_empty() {
-                println!("No saved connections");
-                return Ok(());
+    let keyring_password = if connection.password_source == Some(PasswordSource::Keyring) {
+        let password = connection
+            .password
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("--password-source keyring requires a password"))?;
+        Some(password)
+    } else {
+        None
+    };
+
+    if !args.quiet
+        && let Some(warning) = config.plaintext_password_warning(&alias, &connection)?
+    {
+        eprintln!("Warning: {}", warning);
+    }
+
+    config
+        .with_locked_config(|config| {
+            config.ensure_unchanged(&alias, seen.as_ref())?;
+            if let Some(password) = &keyring_password {
+                DatabaseConnector::store_keyring_password(&alias, password)?;
+            }
+            config.add_connection(alias.clone(), connection)
+        })
+        .context("Failed to add connection config")?;
+
+    println!("Connection config '{}' added", alias);
+
+    Ok(())
+}
+
+/// Change fields of a saved connection (`ddcl edit`)
+fn edit_command(args: EditArgs, config: &mut Config) -> anyhow::Result<()> {
+    // Prompts run before the lock is taken; their answers are kept only if the connection
+    // is still the one the user edited
+    let edited = if args.interactive {
+        let current = config.get_connection(&args.alias)?;
+        let edited =
+            edit_connection_interactively(current).context("Failed in interactive mode input")?;
+        Some((current.clone(), edited))
+    } else {
+        None
+    };
+
+    config
+        .with_locked_config(|config| {
+            let mut connection = match edited {
+                Some((seen, edited)) => {
+                    config.ensure_unchanged(&args.alias, Some(&seen))?;
+                    edited
+                }
+                None => config.get_connection(&args.alias)?.clone(),
+            };
+            args.apply(&mut connection).map_err(|e| {
+                AppError::ValidationError(format!("Failed to apply changes: {}", e))
+            })?;
+
+            // A new password of a keychain connection replaces the keychain entry, as in `add`
+            if connection.password_source == Some(PasswordSource::Keyring)
+                && let Some(password) = connection.password.take()
+            {
+                DatabaseConnector::store_keyring_password(&args.alias, &password)?;
             }
 
-            println!("Connection list:");
-            for (alias, conn) in connections {
-                let status = if DatabaseConnector::check_container(&conn.container).await? {
-                    "Running"
-                } else {
-                    "Stopped"
-                };
+            config.add_connection(args.alias.clone(), connection)
+        })
+        .context("Failed to save connection config")?;
+
+    println!("Connection config '{}' updated", args.alias);
+
+    Ok(())
+}
+
+/// Rename a saved connection, moving its keychain password along (`ddcl rename`)
+fn rename_command(args: RenameArgs, config: &mut Config) -> anyhow::Result<()> {
+    config
+        .with_locked_config(|config| config.rename_connection(&args.old, &args.new))
+        .context("Failed to rename connection config")?;
+
+    let renamed = config.get_connection(&args.new)?;
+    if renamed.password_source == Some(PasswordSource::Keyring) {
+        // The keychain entry is keyed by alias, so it moves with the rename
+        let mut moved = renamed.clone();
+        DatabaseConnector::load_keyring_password(&args.old, &mut moved)
+            .and_then(|()| {
+                DatabaseConnector::store_keyring_password(
+                    &args.new,
+                    moved.password.as_deref().unwrap_or_default(),
+                )
+            })
+            .and_then(|()| DatabaseConnector::delete_keyring_password(&args.old))
+            .context("Failed to move password in the OS keychain")?;
+    }
+
+    println!("Connection config '{}' renamed to '{}'", args.old, args.new);
+
+    Ok(())
+}
+
+/// Copy a saved connection under a new alias (`ddcl duplicate`)
+fn duplicate_command(args: DuplicateArgs, config: &mut Config) -> anyhow::Result<()> {
+    config
+        .with_locked_config(|config| config.duplicate_connection(&args.alias, &args.new_alias))
+        .context("Failed to duplicate connection config")?;
+
+    let copy = config.get_connection(&args.new_alias)?;
+    if copy.password_source == Some(PasswordSource::Keyring) {
+        // The keychain entry is keyed by alias, so the copy needs its own
+        let mut copied = copy.clone();
+        DatabaseConnector::load_keyring_password(&args.alias, &mut copied)
+            .and_then(|()| {
+                DatabaseConnector::store_keyring_password(
+                    &args.new_alias,
+                    copied.password.as_deref().unwrap_or_default(),
+                )
+            })
+            .context("Failed to copy password in the OS keychain")?;
+    }
+
+    println!(
+        "Connection config '{}' duplicated as '{}'",
+        args.alias, args.new_alias
+    );
+
+    Ok(())
+}
+
+/// Remove a saved connection after confirmation (`ddcl remove`)
+fn remove_command(args: RemoveArgs, config: &mut Config) -> anyhow::Result<()> {
+    let seen = config.get_connection(&args.alias)?.clone();
+    let confirmed = confirm_remove(
+        &args.alias,
+        &seen,
+        args.yes,
+        io::stdin().is_terminal(),
+        prompt_confirm,
+    )?;
+    if !confirmed {
+        println!("Aborted");
+        return Ok(());
+    }
+
+    seen.guard_protected(&args.alias, args.force_protected, || {
+        confirm_protected_alias(&args.alias)
+    })?;
+
+    config
+        .with_locked_config(|config| {
+            config.ensure_unchanged(&args.alias, Some(&seen))?;
+            config.remove_connection(&args.alias)
+        })
+        .context("Failed to remove connection config")?;
+
+    if seen.password_source == Some(PasswordSource::Keyring)
+        && let Err(e) = DatabaseConnector::delete_keyring_password(&args.alias)
+    {
+        eprintln!(
+            "Warning: failed to remove the password of '{}' from the OS keychain: {}",
+            args.alias, e
+        );
+    }
+
+    println!("Connection config '{}' removed", args.alias);
+
+    Ok(())
+}
+
+/// Remove all saved connections after confirmation (`ddcl clear`)
+fn clear_command(args: ClearArgs, config: &mut Config) -> anyhow::Result<()> {
+    if config.is_empty() {
+        println!("No saved connections");
+        return Ok(());
+    }
+
+    // Checked again under the lock, where connections saved meanwhile are visible too
+    check_clear_protected(config, args.force_protected)?;
+
+    if !args.yes {
+        if !io::stdin().is_terminal() {
+            return Err(anyhow::anyhow!(
+                "Refusing to remove all connections without confirmation; pass --yes to skip the prompt"
+            ));
+        }
+        let prompt = format!("Remove all {} connections?", config.connections.len());
+        if !prompt_confirm(&prompt)? {
+            println!("Aborted");
+            return Ok(());
+        }
+    }
+
+    let (removed, keyring_aliases) = config
+        .with_locked_config(|config| {
+            check_clear_protected(config, args.force_protected)?;
+            let keyring_aliases: Vec<String> = config
+                .connections
+                .iter()
+                .filter(|(_, connection)| {
+                    connection.password_source == Some(PasswordSource::Keyring)
+                })
+                .map(|(alias, _)| alias.clone())
+                .collect();
+            Ok((config.clear()?, keyring_aliases))
+        })
+        .context("Failed to clear connection configs")?;
+
+    for alias in keyring_aliases {
+        if let Err(e) = DatabaseConnector::delete_keyring_password(&alias) {
+            eprintln!(
+                "Warning: failed to remove the password of '{}' from the OS keychain: {}",
+                alias, e
+            );
+        }
+    }
+
+    println!("Removed {} connection config(s)", removed);
+
+    Ok(())
+}
+
+/// Refuse to clear protected connections unless `--force-protected` is given
+fn check_clear_protected(config: &Config, force_protected: bool) -> Result<()> {
+    let mut protected: Vec<&str> = config
+        .connections
+        .iter()
+        .filter(|(_, connection)| connection.protected)
+        .map(|(alias, _)| alias.as_str())
+        .collect();
+    if !protected.is_empty() && !force_protected {
+        protected.sort();
+        return Err(AppError::Config(format!(
+            "Protected connections would be removed: {}; pass --force-protected to remove them too",
+            protected.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Print saved connections with their container status (`ddcl list`)
+async fn list_command(args: ListArgs, config: &Config) -> anyhow::Result<()> {
+    let mut connections = match (&args.container, &args.tag) {
+        (Some(container), _) => config.find_connections_by_container(container),
+        (None, Some(tag)) => config.list_by_tag(tag),
+        (None, None) => config.list_connections_favorites_first(),
+    };
+    if let (Some(_), Some(tag)) = (&args.container, &args.tag) {
+        connections.retain(|(_, connection)| connection.has_tag(tag));
+    }
+    if args.sort == ListSort::Recent {
+        // Stable, so connections used at the same time (or never) keep their order
+        connections.sort_by_key(|(_, connection)| Reverse(connection.last_used));
+    }
+    let json = args.format == ListFormat::Json;
+
+    if config.is_empty() && !json {
+        println!("No saved connections");
+        return Ok(());
+    }
+
+    let statuses = DatabaseConnector::container_statuses().await?;
+    let mut entries = Vec::new();
+    for (alias, conn) in connections {
+        let status = DatabaseConnector::connection_status(conn, &statuses).await;
+        if args.matches_status(status.is_some_and(DatabaseConnector::is_running_status)) {
+            entries.push((alias, conn, status));
+        }
+    }
+
+    if json {
+        let rows: Vec<_> = entries
+            .iter()
+            .map(|(alias, conn, status)| export::ListEntry::new(alias, conn, *status))
+            .collect();
+        print!(
+            "{}",
+            export::list_to_json(&rows).context("Failed to serialize connections")?
+        );
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No matching connections");
+        return Ok(());
+    }
+
+    println!("Connection list:");
+    for (alias, conn, status) in entries {
+        println!(
+            "{} {}: {} ({}@{}, DB: {}) [{}]{}",
+            if conn.favorite { "★" } else { " " },
+            alias,
+            conn.db_type,
+            conn.user,
+            export::display_container(conn),
+            conn.database.as_deref().unwrap_or("-"),
+            export::display_status(status),
+            export::display_tags(conn)
+        );
+    }
+
+    Ok(())
+}
 
+/// Print running database containers (`ddcl detect`)
+async fn detect_command(args: DetectArgs) -> anyhow::Result<()> {
+    let db_type = args
+        .db_type
+        .as_deref()
+        .map(str::parse::<DatabaseType>)
+        .transpose()?;
+    let containers = DatabaseConnector::detect_database_containers_filtered(db_type, args.limit)
+        .await
+        .context("Failed to detect database containers")?;
+
+    if args.format == ListFormat::Json {
+        print!("{}", export::detected_to_json(&containers)?);
+        return Ok(());
+    }
+
+    if containers.is_empty() {
+        println!("No running database containers detected");
+        return Ok(());
+    }
+
+    println!("Detected database containers:");
+    for container in containers {
+        println!(
+            "  {}: {} ({}, ports: {}) [{}]",
+            container.name,
+            container.db_type,
+            container.image,
+            if container.ports.iter().all(String::is_empty) {
+                "-".to_string()
+            } else {
+                container.ports.join(", ")
+            },
+            container.status
+        );
+    }
+
+    Ok(())
+}
+
+/// Print recently used connections (`ddcl recent`)
+fn recent_command(args: RecentArgs, config: &Config) -> anyhow::Result<()> {
+    let history = History::load_from(&History::get_history_path(&config.path()?))
+        .context("Failed to load connection history")?;
+    // Aliases removed since they were used are skipped
+    let entries: Vec<_> = history
+        .entries
+        .iter()
+        .filter(|entry| config.get_connection(&entry.alias).is_ok())
+        .take(args.limit.unwrap_or(usize::MAX))
+        .collect();
+
+    if entries.is_empty() {
+        println!("No connection history");
+        return Ok(());
+    }
+
+    let now = history::now();
+    for entry in entries {
+        println!(
+            "{}  ({})",
+            entry.alias,
+            history::format_elapsed(entry.timestamp, now)
+        );
+    }
+
+    Ok(())
+}
+
+/// Show, set, or clear the default connection (`ddcl default`)
+fn default_command(args: DefaultArgs, config: &mut Config) -> anyhow::Result<()> {
+    if args.clear {
+        config
+            .with_locked_config(|config| config.set_default_connection(None))
+            .context("Failed to clear the default connection")?;
+        println!("Default connection cleared");
+    } else if let Some(alias) = &args.alias {
+        config
+            .with_locked_config(|config| config.set_default_connection(Some(alias)))
+            .context("Failed to set the default connection")?;
+        println!("Default connection set to '{}'", alias);
+    } else {
+        match &config.default_connection {
+            Some(alias) => println!("{}", alias),
+            None => println!("No default connection set"),
+        }
+    }
+
+    Ok(())
+}
+
+/// List or save connection presets (`ddcl preset`)
+fn preset_command(args: PresetArgs, config: &mut Config) -> anyhow::Result<()> {
+    match args.command {
+        PresetCommands::List => {
+            if config.presets.is_empty() {
+                println!("No saved presets");
+                return Ok(());
+            }
+
+            println!("Preset list:");
+            let mut presets: Vec<_> = config.presets.iter().collect();
+            presets.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, preset) in presets {
+                let mut flags = Vec::new();
+                if preset.read_only {
+                    flags.push("read-only");
+                }
+                if preset.no_pager {
+                    flags.push("no-pager");
+                }
                 println!(
-                    "  {}: {} ({}@{}, DB: {}) [{}]",
-                    alias,
-                    conn.db_type,
-                    conn.user,
-                    conn.container,
-                    conn.database.as_deref().unwrap_or("-"),
-                    status
+                    "  {}: DB: {}, protocol: {}, options: {}, args: {}, flags: {}",
+                    name,
+                    preset.database.as_deref().unwrap_or("-"),
+                    preset
+                        .protocol
+                        .map(|p| p.to_string())
+                        .unwrap_or_else(|| "-".to_string()),
+                    preset.options.as_ref().map_or(0, |o| o.len()),
+                    preset
+                        .extra_args
+                        .as_ref()
+                        .map(|a| a.join(" "))
+                        .unwrap_or_else(|| "-".to_string()),
+                    if flags.is_empty() {
+                        "-".to_string()
+                    } else {
+                        flags.join(", ")
+                    }
                 );
             }
         }
+        PresetCommands::Add(args) => {
+            config
+                .with_locked_config(|config| config.add_preset(args.name.clone(), args.to_preset()))
+                .context("Failed to add preset")?;
+
+            println!("Preset '{}' added", args.name);
+        }
+    }
+
+    Ok(())
+}
+
+/// Dump a database through its client tools in the container (`ddcl dump`)
+async fn dump_command(args: DumpArgs, config: &Config) -> anyhow::Result<()> {
+    let mut connection = config.get_connection(&args.alias)?.clone();
+    DatabaseConnector::load_keyring_password(&args.alias, &mut connection)?;
+    connection.apply_env_file()?;
+    connection.container = DatabaseConnector::resolve_container(&connection).await?;
+
+    DatabaseConnector::dump(&connection, args.dump_format, args.output.as_deref())
+        .await
+        .context("Failed to dump database")?;
+
+    if let Some(output) = &args.output {
+        eprintln!("Dumped '{}' to {}", args.alias, output.display());
+    }
+
+    Ok(())
+}
+
+/// Print a saved connection as a connection string (`ddcl uri`)
+fn uri_command(args: UriArgs, config: &mut Config) -> anyhow::Result<()> {
+    // Keychain passwords are only read when they are printed; nothing is saved
+    if args.reveal
+        && let Some(connection) = config.connections.get_mut(&args.alias)
+    {
+        DatabaseConnector::load_keyring_password(&args.alias, connection)?;
+    }
+    println!(
+        "{}",
+        config.export_connection_string(&args.alias, Some(&args.host), args.port, args.reveal)?
+    );
+
+    Ok(())
+}
+
+/// Run a single query and print its output (`ddcl exec`)
+async fn exec_command(args: ExecArgs, config: &Config) -> anyhow::Result<()> {
+    let mut connection = config.get_connection(&args.alias)?.clone();
+    DatabaseConnector::load_keyring_password(&args.alias, &mut connection)?;
+    connection.apply_env_file()?;
+    connection.container = DatabaseConnector::resolve_container(&connection).await?;
+
+    let output = DatabaseConnector::exec_query(&connection, &args.query(), args.json)
+        .await
+        .context("Failed to run query")?;
+    print!("{}", output);
+
+    Ok(())
+}
+
+/// Check that a saved connection accepts connections (`ddcl test`)
+async fn test_command(args: TestArgs, config: &Config) -> anyhow::Result<()> {
+    let mut connection = config.get_connection(&args.alias)?.clone();
+    DatabaseConnector::load_keyring_password(&args.alias, &mut connection)?;
+    connection.apply_env_file()?;
+    connection.container = DatabaseConnector::resolve_container(&connection).await?;
+
+    let ok = DatabaseConnector::test_connection(&connection)
+        .await
+        .context("Failed to run connection test")?;
+    if ok {
+        println!("PASS: '{}' accepts connections", args.alias);
+    } else {
+        eprintln!("FAIL: '{}' did not respond", args.alias);
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Print the fields of a saved connection (`ddcl show`)
+fn show_command(args: ShowArgs, config: &Config) -> anyhow::Result<()> {
+    let conn = config.get_connection(&args.alias)?;
+    let conn = if args.reveal {
+        conn.clone()
+    } else {
+        conn.masked()
+    };
+
+    if let Some(field) = &args.field {
+        if field == "password" && !args.reveal {
+            return Err(anyhow::anyhow!("Printing the password requires --reveal"));
+        }
+        println!("{}", conn.field(field)?.unwrap_or_default());
+        return Ok(());
+    }
+
+    if args.format == ShowFormat::Json {
+        print!("{}", export::show_to_json(&args.alias, &conn)?);
+        return Ok(());
+    }
+
+    println!("{}:", args.alias);
+    for field in DatabaseConnection::FIELDS {
+        if let Some(value) = conn.field(field)? {
+            println!("  {}: {}", field, value);
+        }
+    }
+
+    Ok(())
+}
+
+/// Export saved connections as JSON or a shell script (`ddcl export`)
+fn export_command(args: ExportArgs, config: &Config) -> anyhow::Result<()> {
+    let redacted;
+    let config = if args.redact {
+        redacted = config.redacted();
+        &redacted
+    } else {
+        config
+    };
+    let exported = match args.format {
+        ExportFormat::Json => export::to_json(config),
+        ExportFormat::Sh => export::to_shell_script(config),
+    }
+    .context("Failed to export connections")?;
+
+    match &args.output {
+        Some(path) => {
+            fs::write(path, exported)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!(
+                "Exported {} connections to {}",
+                config.len(),
+                path.display()
+            );
+        }
+        None => print!("{}", exported),
+    }
+
+    Ok(())
+}
+
+/// Import connections from a JSON or YAML file (`ddcl import`)
+fn import_command(args: ImportArgs, config: &mut Config) -> anyhow::Result<()> {
+    let content = fs::read_to_string(&args.path)
+        .with_context(|| format!("Failed to read {}", args.path.display()))?;
+    let extension = args
+        .path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase);
+    let imported: Config = match extension.as_deref() {
+        Some("json") => serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", args.path.display()))?,
+        Some("yaml" | "yml") => serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", args.path.display()))?,
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Cannot detect the format of {} (expected .json, .yaml, or .yml)",
+                args.path.display()
+            ));
+        }
+    };
+
+    let summary = config
+        .with_locked_config(|config| {
+            config.import_connections(imported.connections, args.on_conflict, args.keep_hooks)
+        })
+        .context("Failed to import connections")?;
+
+    println!("Import finished: {}", summary);
+
+    Ok(())
+}
+
+/// List config profiles, marking the current one (`ddcl profiles`)
+fn profiles_command(current: Option<&str>) -> anyhow::Result<()> {
+    let profiles = Config::list_profiles().context("Failed to list profiles")?;
+    if profiles.is_empty() {
+        println!("No profiles. Use --profile <name> to create one.");
+    }
+    for profile in profiles {
+        let marker = if current == Some(profile.as_str()) {
+            "*"
+        } else {
+            " "
+        };
+        println!("{} {}", marker, profile);
+    }
+
+    Ok(())
+}
+
+/// Print the shell completion script (`ddcl completion`)
+fn completion_command(args: CompletionArgs) -> anyhow::Result<()> {
+    let aliases =
+        if args.aliases {
+            Some(completion::alias_script(args.shell).ok_or_else(|| {
+                anyhow::anyhow!("--aliases is not supported for {:?}", args.shell)
+            })?)
+        } else {
+            None
+        };
+
+    print!("{}", completion::static_script(args.shell));
+    if let Some(aliases) = aliases {
+        print!("{}", aliases);
+    }
+
+    Ok(())
+}
+
+/// Report invalid saved connections (`ddcl validate`)
+fn validate_command(config: &Config) -> anyhow::Result<()> {
+    let problems = config.validation_problems();
+    if problems.is_empty() {
+        println!("OK: {} connection(s) valid", config.connections.len());
+        return Ok(());
+    }
+
+    for (alias, problem) in &problems {
+        eprintln!("{}: {}", alias, problem);
+    }
+    eprintln!("Found {} problem(s)", problems.len());
+    process::exit(1);
+}
+
+/// Print one ✓/✗ line per environment check, with a hint for failures (`ddcl doctor`)
+///
+/// Returns whether every check passed. Checks that depend on a failed one are skipped.
+async fn doctor(config_path: &Path) -> bool {
+    let mut healthy = true;
+    let mut report = |result: std::result::Result<String, String>, hint: &str| match result {
+        Ok(message) => println!("✓ {}", message),
+        Err(message) => {
+            println!("✗ {}", message);
+            println!("  {}", hint);
+            healthy = false;
+        }
+    };
+
+    let docker = DatabaseConnector::docker_client_version().await;
+    let docker_found = docker.is_ok();
+    report(
+        docker.map_err(|e| e.to_string()),
+        "Install Docker (https://docs.docker.com/get-docker/) and make sure `docker` is in PATH",
+    );
+
+    let daemon = if docker_found {
+        let daemon = DatabaseConnector::docker_server_version().await;
+        let reachable = daemon.is_ok();
+        report(
+            daemon
+                .map(|version| format!("Docker daemon reachable (server {})", version))
+                .map_err(|e| e.to_string()),
+            "Start Docker Desktop or the docker service (e.g. `sudo systemctl start docker`), \
+             and check DOCKER_HOST and your access to the docker socket",
+        );
+        reachable
+    } else {
+        false
+    };
+
+    report(
+        doctor_config(config_path),
+        "Fix or move the file aside; `ddcl validate` lists invalid connections",
+    );
+
+    report(
+        if io::stdin().is_terminal() && io::stdout().is_terminal() {
+            Ok("Running in a terminal".to_string())
+        } else {
+            Err("Not running in a terminal".to_string())
+        },
+        "Interactive clients need a TTY; use `ddcl connect --no-tty` in pipes and CI",
+    );
+
+    if daemon {
+        report(
+            match DatabaseConnector::detect_database_containers().await {
+                Ok(containers) if containers.is_empty() => {
+                    Err("No running database containers detected".to_string())
+                }
+                Ok(containers) => Ok(format!(
+                    "{} running database container(s) detected",
+                    containers.len()
+                )),
+                Err(e) => Err(e.to_string()),
+            },
+            "Start your database containers (e.g. `docker compose up -d`) and check `docker ps`",
+        );
     }
 
+    healthy
+}
+
+/// Describe whether the config file loads and its connections pass validation
+fn doctor_config(config_path: &Path) -> std::result::Result<String, String> {
+    if !config_path.exists() {
+        return Ok(format!(
+            "Config file {} not created yet (`ddcl add` creates it)",
+            config_path.display()
+        ));
+    }
+
+    let config = Config::load_from_with_key(config_path, crypto::master_key_from_env())
+        .map_err(|e| format!("Config file {}: {}", config_path.display(), e))?;
+    let problems = config.validation_problems().len();
+    if problems > 0 {
+        return Err(format!(
+            "Config file {}: {} problem(s) in saved connections",
+            config_path.display(),
+            problems
+        ));
+    }
+    Ok(format!(
+        "Config file {} ({} connection(s))",
+        config_path.display(),
+        config.connections.len()
+    ))
+}
+
+/// Pick a saved connection from a menu and connect to it (`ddcl` without a subcommand)
+async fn launch(config: &mut Config) -> anyhow::Result<()> {
+    if !io::stdin().is_terminal() {
+        return Err(anyhow::anyhow!(
+            "No subcommand given. Run `ddcl --help` for usage"
+        ));
+    }
+    if config.is_empty() {
+        println!("No saved connections. Add one with `ddcl add`");
+        return Ok(());
+    }
+
+    let statuses = DatabaseConnector::container_statuses().await?;
+    let mut aliases = Vec::new();
+    let mut items = Vec::new();
+    for (alias, conn) in config.list_connections_favorites_first() {
+        let status =
+            export::display_status(DatabaseConnector::connection_status(conn, &statuses).await);
+        items.push(format!(
+            "{} {}: {} ({}@{}) [{}]",
+            if conn.favorite { "★" } else { " " },
+            alias,
+            conn.db_type,
+            conn.user,
+            export::display_container(conn),
+            status
+        ));
+        aliases.push(alias.clone());
+    }
+
+    let Some(selection) = select_connection(&items)? else {
+        return Ok(());
+    };
+    let docker_host = config
+        .get_connection(&aliases[selection])?
+        .docker_host
+        .clone();
+    let args = ConnectArgs {
+        alias: Some(aliases[selection].clone()),
+        ..Default::default()
+    };
+    db::scope_docker_host(docker_host, connect_command(args, config)).await?;
     Ok(())
 }
 
-async fn connect_command(args: ConnectArgs, config: &Config) -> Result<()> {
-    let connection = if let Some(alias) = args.alias {
-        config.get_connection(&alias)?.clone()
+/// Docker host of the connection a command talks to: `--docker-host`, then the alias's setting
+fn command_docker_host(command: &Commands, config: &Config) -> Option<String> {
+    let alias = match command {
+        Commands::Connect(args) if args.docker_host.is_some() => return args.docker_host.clone(),
+        Commands::Connect(args) => args.alias.as_ref()?,
+        Commands::Dump(args) => &args.alias,
+        Commands::Exec(args) => &args.alias,
+        Commands::Test(args) => &args.alias,
+        Commands::Logs(args) => &args.alias,
+        _ => return None,
+    };
+    config
+        .connections
+        .get(alias)
+        .and_then(|connection| connection.docker_host.clone())
+}
+
+async fn connect_command(mut args: ConnectArgs, config: &mut Config) -> Result<()> {
+    if args.password_stdin {
+        args.password = Some(read_password_line(io::stdin().lock())?);
+    }
+
+    if args.alias.is_none() && !args.interactive && !args.has_connection_params() {
+        args.alias = Some(config.default_alias()?.to_string());
+    }
+
+    let unsaved = if args.interactive {
+        Some(get_unsaved_connection_interactively().await?)
+    } else {
+        None
+    };
+
+    let mut connection = if let Some(connection) = &unsaved {
+        connection.clone()
+    } else if let Some(alias) = &args.alias {
+        let mut connection = config.get_connection(alias)?.clone();
+        if args.password_stdin {
+            connection.password = args.password.clone();
+            connection.password_source = None;
+        } else {
+            DatabaseConnector::load_keyring_password(alias, &mut connection)?;
+        }
+        connection
     } else if let Some(connection) = args.to_connection() {
         connection
     } else {
@@ -114,16 +1101,209 @@ async fn connect_command(args: ConnectArgs, config: &Config) -> Result<()> {
         process::exit(1);
     };
 
-    if !DatabaseConnector::check_container(&connection.container).await? {
-        eprintln!("Error: Container '{}' is not running", connection.container);
-        process::exit(1);
+    connection.apply_env_file()?;
+
+    if let Some(preset) = &args.preset {
+        config.get_preset(preset)?.apply(&mut connection)?;
+    }
+
+    resolve_compose(&args, &mut connection).await?;
+
+    apply_overrides(&args, &mut connection);
+
+    connection.container = DatabaseConnector::resolve_container(&connection).await?;
+
+    // --wait covers both the container starting and the database becoming ready
+    let wait_deadline = args
+        .wait
+        .map(|secs| std::time::Instant::now() + Duration::from_secs(secs));
+
+    // `docker compose ps -q` only lists running containers, by ID rather than name
+    if !args.compose && !args.dry_run && !args.copy_to_clipboard {
+        require_running(&connection.container, args.wait).await?;
+    }
+
+    if args.detect_port && connection.port.is_none() {
+        match DatabaseConnector::get_published_port(&connection.container, &connection.db_type)
+            .await?
+        {
+            Some(mapping) => connection.port = Some(mapping.container_port),
+            None => eprintln!(
+                "Warning: No published port found for container '{}', using default port",
+                connection.container
+            ),
+        }
     }
 
+    // Printed commands are run later, so only --no-tty decides whether they allocate a TTY
+    let tty = !args.no_tty;
+
+    if args.dry_run {
+        return DatabaseConnector::connect(&connection, true, tty).await;
+    }
+
+    if args.copy_to_clipboard {
+        let command = DatabaseConnector::command_line(&connection, tty)?;
+        arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(command))?;
+        eprintln!("Copied the docker command to the clipboard");
+        return Ok(());
+    }
+
+    config.check_hooks(&connection)?;
+
+    if args.wait_ready
+        && let Some(deadline) = wait_deadline
+    {
+        println!(
+            "Waiting for {} in '{}' to accept connections...",
+            connection.db_type, connection.container
+        );
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        if !DatabaseConnector::wait_until_ready(&connection, remaining).await? {
+            eprintln!(
+                "Error: {} in '{}' did not accept connections within {}s",
+                connection.db_type,
+                connection.container,
+                args.wait.unwrap_or_default()
+            );
+            process::exit(1);
+        }
+    }
+
+    DatabaseConnector::resolve_client_binary(&mut connection).await;
+
     println!(
         "Connecting to {} container '{}'...",
         connection.db_type, connection.container
     );
-    DatabaseConnector::connect(&connection).await?;
+    log::info!(
+        alias = args.alias.as_deref().unwrap_or_default(),
+        container = connection.container.as_str(),
+        db_type:% = connection.db_type;
+        "Connecting"
+    );
+    let session = session(&connection, args.record.as_deref(), tty);
+    DatabaseConnector::with_hooks(&connection, session).await?;
+
+    if let Some(alias) = &args.alias {
+        if let Err(e) = config
+            .path()
+            .and_then(|path| History::record_connection(&path, alias))
+        {
+            eprintln!("Warning: failed to record connection history: {}", e);
+        }
+        if let Err(e) =
+            config.with_locked_config(|config| config.record_last_used(alias, Utc::now()))
+        {
+            eprintln!("Warning: failed to record last use of '{}': {}", alias, e);
+        }
+    }
+
+    if let Some(connection) = unsaved
+        && let Some(alias) = save_after_connect(
+            config,
+            connection,
+            args.no_save,
+            prompt_save_alias,
+            |preview| {
+                println!("Changes to the saved connection:\n{}", preview);
+                prompt_confirm("Overwrite the saved connection?")
+            },
+            confirm_protected_alias,
+        )?
+    {
+        println!("Connection config '{}' added", alias);
+    }
 
     Ok(())
 }
+
+/// Exit with a hint unless `container` is running, first waiting up to `wait` seconds for it
+async fn require_running(container: &str, wait: Option<u64>) -> Result<()> {
+    let mut running = DatabaseConnector::require_container(container).await;
+    if let (Err(AppError::ContainerNotFound(_)), Some(secs)) = (&running, wait) {
+        println!(
+            "Waiting up to {}s for container '{}' to start...",
+            secs, container
+        );
+        running = DatabaseConnector::wait_for_container(container, Duration::from_secs(secs)).await;
+    }
+    if let Err(AppError::ContainerNotFound(name)) = &running {
+        // `docker ps -a` tells a stopped container from a missing one
+        eprintln!(
+            "Error: {}",
+            DatabaseConnector::not_running_message(name).await?
+        );
+        process::exit(1);
+    }
+    running
+}
+
+/// Apply the `connect` flags that override fields of the connection
+fn apply_overrides(args: &ConnectArgs, connection: &mut DatabaseConnection) {
+    if let Some(entrypoint) = args.entrypoint.as_deref().and_then(parse_entrypoint) {
+        connection.entrypoint = Some(entrypoint);
+    }
+
+    if let Some(exec_user) = &args.exec_user {
+        connection.exec_user = Some(exec_user.clone());
+    }
+
+    // Explicit connections already carry --env/--option; for saved aliases they override per key
+    if args.alias.is_some() && !args.env.is_empty() {
+        connection
+            .env
+            .get_or_insert_with(HashMap::new)
+            .extend(args.env.iter().cloned());
+    }
+
+    if args.alias.is_some() && !args.options.is_empty() {
+        connection
+            .options
+            .get_or_insert_with(HashMap::new)
+            .extend(args.options.iter().cloned());
+    }
+}
+
+/// Replace the container with the one behind `--compose-service` or `--compose`
+async fn resolve_compose(args: &ConnectArgs, connection: &mut DatabaseConnection) -> Result<()> {
+    if let Some(service) = &args.compose_service {
+        println!(
+            "Waiting for Compose service '{}' to become ready...",
+            service
+        );
+        connection.container = DatabaseConnector::wait_for_compose_service(
+            service,
+            args.project_directory.as_deref(),
+            Duration::from_secs(args.wait_timeout),
+        )
+        .await?;
+    }
+
+    if args.compose {
+        connection.container = DatabaseConnector::resolve_compose_service(
+            &connection.container,
+            args.project_directory.as_deref(),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Run the client interactively, or with its output recorded to `record`
+async fn session(connection: &DatabaseConnection, record: Option<&Path>, tty: bool) -> Result<()> {
+    match record {
+        Some(record) => {
+            eprintln!(
+                "Recording session to {} (no TTY: prompts and line editing are unavailable)",
+                record.display()
+            );
+            DatabaseConnector::connect_recorded(connection, record).await
+        }
+        // `docker exec -t` fails with "the input device is not a TTY" when stdin is piped
+        None => {
+            DatabaseConnector::connect(connection, false, tty && io::stdin().is_terminal()).await
+        }
+    }
+}
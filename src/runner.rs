@@ -0,0 +1,291 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::Arc;
+
+use crate::db::docker_command;
+
+/// Boxed future returned by [`CommandRunner`] methods
+pub type RunnerFuture<'a, T> = Pin<Box<dyn Future<Output = std::io::Result<T>> + Send + 'a>>;
+
+/// Exit status of a finished command
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommandStatus {
+    /// Whether the command exited successfully
+    pub success: bool,
+    /// Exit code (`None` when terminated by a signal)
+    pub code: Option<i32>,
+}
+
+impl From<std::process::ExitStatus> for CommandStatus {
+    fn from(status: std::process::ExitStatus) -> Self {
+        Self {
+            success: status.success(),
+            code: status.code(),
+        }
+    }
+}
+
+impl std::fmt::Display for CommandStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "exit code {}", code),
+            None => write!(f, "terminated by signal"),
+        }
+    }
+}
+
+/// Captured output of a finished command
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    /// Exit status
+    pub status: CommandStatus,
+    /// Captured standard output
+    pub stdout: Vec<u8>,
+    /// Captured standard error
+    pub stderr: Vec<u8>,
+}
+
+impl From<std::process::Output> for CommandOutput {
+    fn from(output: std::process::Output) -> Self {
+        Self {
+            status: output.status.into(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        }
+    }
+}
+
+/// Executes `docker` CLI invocations
+pub trait CommandRunner: Send + Sync {
+    /// Run `docker <args>` and capture its output
+    fn output<'a>(&'a self, args: &'a [String]) -> RunnerFuture<'a, CommandOutput>;
+
+    /// Run `docker <args>` attached to the terminal
+    fn status<'a>(&'a self, args: &'a [String]) -> RunnerFuture<'a, CommandStatus>;
+
+    /// Run `docker <args>` without stdin, writing its stdout to `stdout` (the terminal if `None`)
+    ///
+    /// Runners that don't care where the output goes can keep the default, which behaves
+    /// like [`CommandRunner::status`].
+    fn status_to<'a>(
+        &'a self,
+        args: &'a [String],
+        stdout: Option<std::fs::File>,
+    ) -> RunnerFuture<'a, CommandStatus> {
+        drop(stdout);
+        self.status(args)
+    }
+}
+
+/// Runner invoking the real `docker` binary
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DockerRunner;
+
+impl CommandRunner for DockerRunner {
+    fn output<'a>(&'a self, args: &'a [String]) -> RunnerFuture<'a, CommandOutput> {
+        Box::pin(async move {
+            // Timed-out queries drop this future; don't leave docker running behind
+            let output = docker_command()
+                .args(args)
+                .kill_on_drop(true)
+                .output()
+                .await?;
+            Ok(output.into())
+        })
+    }
+
+    fn status<'a>(&'a self, args: &'a [String]) -> RunnerFuture<'a, CommandStatus> {
+        Box::pin(async move {
+            let status = docker_command()
+                .args(args)
+                .stdin(Stdio::inherit())
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit())
+                .status()
+                .await?;
+            Ok(status.into())
+        })
+    }
+
+    fn status_to<'a>(
+        &'a self,
+        args: &'a [String],
+        stdout: Option<std::fs::File>,
+    ) -> RunnerFuture<'a, CommandStatus> {
+        Box::pin(async move {
+            let stdout = stdout.map_or_else(Stdio::inherit, Stdio::from);
+            let status = docker_command()
+                .args(args)
+                .stdin(Stdio::null())
+                .stdout(stdout)
+                .stderr(Stdio::inherit())
+                .status()
+                .await?;
+            Ok(status.into())
+        })
+    }
+}
+
+tokio::task_local! {
+    static RUNNER: Arc<dyn CommandRunner>;
+}
+
+/// Run a future with `runner` used for every docker invocation made inside it
+pub async fn scope<F: Future>(runner: Arc<dyn CommandRunner>, f: F) -> F::Output {
+    RUNNER.scope(runner, f).await
+}
+
+/// Get the runner for the current task
+pub fn current() -> Arc<dyn CommandRunner> {
+    if let Ok(runner) = RUNNER.try_with(Arc::clone) {
+        return runner;
+    }
+
+    #[cfg(any(test, feature = "dev-stub"))]
+    if std::env::var("DDCL_NO_DOCKER").is_ok_and(|value| value == "1") {
+        return Arc::new(StubRunner);
+    }
+
+    Arc::new(DockerRunner)
+}
+
+/// Runner returning canned docker responses, enabled with `DDCL_NO_DOCKER=1`
+///
+/// Only compiled for tests or with the `dev-stub` feature so release builds always talk to docker.
+#[cfg(any(test, feature = "dev-stub"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StubRunner;
+
+#[cfg(any(test, feature = "dev-stub"))]
+impl StubRunner {
+    /// Canned `docker ps` rows: name, image, ports, status
+    pub const CONTAINERS: &'static [(&'static str, &'static str, &'static str, &'static str)] = &[
+        (
+            "ddcl-postgres",
+            "postgres:16",
+            "0.0.0.0:5432->5432/tcp",
+            "Up 5 minutes",
+        ),
+        (
+            "ddcl-mysql",
+            "mysql:8",
+            "0.0.0.0:3306->3306/tcp",
+            "Up 5 minutes",
+        ),
+        (
+            "ddcl-mongo",
+            "mongo:7",
+            "0.0.0.0:27017->27017/tcp",
+            "Up 5 minutes",
+        ),
+    ];
+
+    fn stdout_for(args: &[String]) -> String {
+        match args.first().map(String::as_str) {
+            Some("ps") if args.iter().any(|arg| arg.contains("{{.Image}}")) => Self::CONTAINERS
+                .iter()
+                .map(|(name, image, ports, status)| {
+                    format!("{}\t{}\t{}\t{}\n", name, image, ports, status)
+                })
+                .collect(),
+            Some("ps") if args.iter().any(|arg| arg.contains("{{.Status}}")) => Self::CONTAINERS
+                .iter()
+                .map(|(name, _, _, status)| format!("{}\t{}\n", name, status))
+                .collect(),
+            Some("ps") if args.iter().any(|arg| arg.starts_with("label=")) => {
+                format!("{}\n", Self::CONTAINERS[0].0)
+            }
+            Some("ps") => Self::CONTAINERS
+                .iter()
+                .map(|(name, ..)| format!("{}\n", name))
+                .collect(),
+            Some("compose") if args.iter().any(|arg| arg == "-q") => "0123456789ab\n".to_string(),
+            Some("compose") => format!(
+                "{{\"Name\":\"{}\",\"Service\":\"db\",\"State\":\"running\",\"Health\":\"healthy\"}}\n",
+                Self::CONTAINERS[0].0
+            ),
+            Some("port") => "5432/tcp -> 0.0.0.0:5432\n".to_string(),
+            Some("--version") => "Docker version 27.0.3, build stub\n".to_string(),
+            Some("info") => "27.0.3\n".to_string(),
+            Some("exec") if args.iter().any(|arg| arg == "mktemp") => {
+                "/tmp/ddcl-dump.Ab12Cd\n".to_string()
+            }
+            Some("exec") if args.last().is_some_and(|arg| arg == "env") => {
+                "POSTGRES_USER=postgres\nPOSTGRES_DB=postgres\n".to_string()
+            }
+            _ => String::new(),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "dev-stub"))]
+impl CommandRunner for StubRunner {
+    fn output<'a>(&'a self, args: &'a [String]) -> RunnerFuture<'a, CommandOutput> {
+        Box::pin(async move {
+            Ok(CommandOutput {
+                status: CommandStatus {
+                    success: true,
+                    code: Some(0),
+                },
+                stdout: Self::stdout_for(args).into_bytes(),
+                stderr: Vec::new(),
+            })
+        })
+    }
+
+    fn status<'a>(&'a self, _args: &'a [String]) -> RunnerFuture<'a, CommandStatus> {
+        Box::pin(async move {
+            Ok(CommandStatus {
+                success: true,
+                code: Some(0),
+            })
+        })
+    }
+}
+
+/// Runner recording every docker invocation, answering with [`StubRunner`]'s canned responses
+///
+/// Lets tests assert the exact argv a command produces without Docker installed.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct RecordingRunner {
+    calls: std::sync::Mutex<Vec<Vec<String>>>,
+}
+
+#[cfg(test)]
+impl RecordingRunner {
+    /// Arguments of every invocation so far, in call order
+    pub fn calls(&self) -> Vec<Vec<String>> {
+        self.calls.lock().expect("calls lock poisoned").clone()
+    }
+
+    fn record(&self, args: &[String]) {
+        self.calls
+            .lock()
+            .expect("calls lock poisoned")
+            .push(args.to_vec());
+    }
+}
+
+#[cfg(test)]
+impl CommandRunner for RecordingRunner {
+    fn output<'a>(&'a self, args: &'a [String]) -> RunnerFuture<'a, CommandOutput> {
+        self.record(args);
+        StubRunner.output(args)
+    }
+
+    fn status<'a>(&'a self, args: &'a [String]) -> RunnerFuture<'a, CommandStatus> {
+        self.record(args);
+        StubRunner.status(args)
+    }
+
+    fn status_to<'a>(
+        &'a self,
+        args: &'a [String],
+        stdout: Option<std::fs::File>,
+    ) -> RunnerFuture<'a, CommandStatus> {
+        self.record(args);
+        StubRunner.status_to(args, stdout)
+    }
+}
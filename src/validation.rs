@@ -1,14 +1,43 @@
+use std::future::Future;
+
+use crate::config::DatabaseType;
 use crate::error::AppError;
 use regex::Regex;
 
+tokio::task_local! {
+    static BYPASS: bool;
+}
+
+/// Run a future with container, username, and database name validation bypassed (`--no-validate`)
+///
+/// Docker arguments are passed to `Command` directly rather than through a shell, so skipping
+/// validation does not allow shell injection. It does allow values such as a leading `-` that
+/// docker or the database client may misread as a flag, so only use it for trusted inputs.
+pub async fn scope_bypass<F: Future>(f: F) -> F::Output {
+    BYPASS.scope(true, f).await
+}
+
+/// Whether name validation is bypassed for the current task
+fn bypassed() -> bool {
+    BYPASS.try_with(|bypass| *bypass).unwrap_or(false)
+}
+
 /// Validates container name to prevent command injection
+///
+/// Follows docker's own naming rule (`/?[a-zA-Z0-9][a-zA-Z0-9_.-]+`), including the optional
+/// leading slash shown by `docker inspect`. Arguments are passed to `Command` directly rather than
+/// through a shell, so the main risk is a leading `-` being parsed as a docker flag.
 pub fn validate_container_name(name: &str) -> Result<(), AppError> {
+    if bypassed() {
+        return Ok(());
+    }
+
     let valid_pattern =
-        Regex::new(r"^[a-zA-Z0-9][a-zA-Z0-9_.-]*$").expect("Failed to compile regex");
+        Regex::new(r"^/?[a-zA-Z0-9][a-zA-Z0-9_.-]*$").expect("Failed to compile regex");
 
     if !valid_pattern.is_match(name) {
         return Err(AppError::ValidationError(
-            "Invalid container name. Only alphanumeric characters, dots, hyphens, and underscores are allowed".to_string()
+            "Invalid container name. Must start with an alphanumeric character (optionally after a leading '/') and contain only alphanumeric characters, dots, hyphens, and underscores".to_string()
         ));
     }
 
@@ -21,8 +50,29 @@ pub fn validate_container_name(name: &str) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Validates a container ID or ID prefix: lowercase hex, at most the 64 characters of a full ID
+///
+/// Used to decide whether an unknown container name may be an ID prefix, so `--no-validate`
+/// does not apply.
+pub fn validate_container_id(id: &str) -> Result<(), AppError> {
+    let valid_pattern = Regex::new(r"^[0-9a-f]{1,64}$").expect("Failed to compile regex");
+
+    if !valid_pattern.is_match(id) {
+        return Err(AppError::ValidationError(format!(
+            "Invalid container ID '{}'. Must be 1 to 64 lowercase hexadecimal characters",
+            id
+        )));
+    }
+
+    Ok(())
+}
+
 /// Validates database username to prevent command injection
 pub fn validate_username(username: &str) -> Result<(), AppError> {
+    if bypassed() {
+        return Ok(());
+    }
+
     let valid_pattern = Regex::new(r"^[a-zA-Z][a-zA-Z0-9_.-]*$").expect("Failed to compile regex");
 
     if !valid_pattern.is_match(username) {
@@ -42,6 +92,10 @@ pub fn validate_username(username: &str) -> Result<(), AppError> {
 
 /// Validates database name to prevent command injection
 pub fn validate_database_name(name: &str) -> Result<(), AppError> {
+    if bypassed() {
+        return Ok(());
+    }
+
     let valid_pattern = Regex::new(r"^[a-zA-Z][a-zA-Z0-9_]*$").expect("Failed to compile regex");
 
     if !valid_pattern.is_match(name) {
@@ -59,6 +113,308 @@ pub fn validate_database_name(name: &str) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Validates the path of a SQLite database file inside the container
+///
+/// Unlike server database names, slashes are allowed so the path can point anywhere in the
+/// container. A leading `-` is rejected so the path is not parsed as a `sqlite3` option.
+pub fn validate_sqlite_path(path: &str) -> Result<(), AppError> {
+    if bypassed() {
+        return Ok(());
+    }
+
+    let valid_pattern =
+        Regex::new(r"^[a-zA-Z0-9_./][a-zA-Z0-9_./-]*$").expect("Failed to compile regex");
+
+    if !valid_pattern.is_match(path) {
+        return Err(AppError::ValidationError(
+            "Invalid SQLite database path. Must not start with '-' and contain only alphanumeric characters, slashes, dots, hyphens, and underscores".to_string()
+        ));
+    }
+
+    if path.len() > 4096 {
+        return Err(AppError::ValidationError(
+            "SQLite database path is too long (max 4096 characters)".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validates the path of the database client executable inside the container
+pub fn validate_client_path(path: &str) -> Result<(), AppError> {
+    let valid_pattern =
+        Regex::new(r"^[a-zA-Z0-9_./][a-zA-Z0-9_./-]*$").expect("Failed to compile regex");
+
+    if !valid_pattern.is_match(path) {
+        return Err(AppError::ValidationError(
+            "Invalid client path. Must not start with '-' and contain only alphanumeric characters, slashes, dots, hyphens, and underscores".to_string()
+        ));
+    }
+
+    if path.len() > 4096 {
+        return Err(AppError::ValidationError(
+            "Client path is too long (max 4096 characters)".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validates alias name so it is usable as a YAML key, a command-line argument, and a file name
+///
+/// Letters (including non-ASCII), digits, `-`, `_`, and `.` are allowed; the name must not
+/// start with `-` (it would parse as a flag) or `.` (hidden files, `..`).
+pub fn validate_alias(name: &str) -> Result<(), AppError> {
+    if name.is_empty() {
+        return Err(AppError::ValidationError(
+            "Alias name must not be empty".to_string(),
+        ));
+    }
+
+    if name.trim() != name {
+        return Err(AppError::ValidationError(
+            "Alias name must not have leading or trailing whitespace".to_string(),
+        ));
+    }
+
+    if name.len() > 64 {
+        return Err(AppError::ValidationError(
+            "Alias name is too long (max 64 characters)".to_string(),
+        ));
+    }
+
+    if name.starts_with(['-', '.']) {
+        return Err(AppError::ValidationError(format!(
+            "Alias name '{}' must not start with '-' or '.'",
+            name
+        )));
+    }
+
+    if let Some(c) = name
+        .chars()
+        .find(|&c| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '.')))
+    {
+        return Err(AppError::ValidationError(format!(
+            "Invalid character {:?} in alias name '{}': only letters, digits, '-', '_' and '.' are allowed",
+            c, name
+        )));
+    }
+
+    Ok(())
+}
+
+/// Validates a config profile name, which becomes part of the `config.<name>.yaml` file name
+pub fn validate_profile_name(name: &str) -> Result<(), AppError> {
+    if name.is_empty() {
+        return Err(AppError::ValidationError(
+            "Profile name must not be empty".to_string(),
+        ));
+    }
+
+    if name.len() > 64 {
+        return Err(AppError::ValidationError(
+            "Profile name is too long (max 64 characters)".to_string(),
+        ));
+    }
+
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(AppError::ValidationError(format!(
+            "Invalid profile name '{}': only letters, digits, '-' and '_' are allowed",
+            name
+        )));
+    }
+
+    Ok(())
+}
+
+/// Validates the working directory used for `docker exec -w`
+pub fn validate_working_dir(path: &str) -> Result<(), AppError> {
+    if !path.starts_with('/') {
+        return Err(AppError::ValidationError(
+            "Working directory must be an absolute path".to_string(),
+        ));
+    }
+
+    if path.chars().any(char::is_control) {
+        return Err(AppError::ValidationError(
+            "Working directory must not contain control characters".to_string(),
+        ));
+    }
+
+    if path.len() > 4096 {
+        return Err(AppError::ValidationError(
+            "Working directory is too long (max 4096 characters)".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validates an extra `docker exec` argument from `exec_options`
+///
+/// Each entry is a single argument passed to docker as-is. Shell metacharacters are rejected
+/// so the values stay safe when the command is copied into a shell script by `export`.
+pub fn validate_exec_option(option: &str) -> Result<(), AppError> {
+    if option.is_empty() {
+        return Err(AppError::ValidationError(
+            "docker exec option must not be empty".to_string(),
+        ));
+    }
+
+    let metacharacters =
+        Regex::new(r#"[;&|$`<>\\'"*?(){}\[\]!#~]"#).expect("Failed to compile regex");
+    if metacharacters.is_match(option) || option.chars().any(|c| c.is_control() || c == ' ') {
+        return Err(AppError::ValidationError(format!(
+            "Invalid docker exec option '{}'. Shell metacharacters and whitespace are not allowed",
+            option
+        )));
+    }
+
+    if option.len() > 4096 {
+        return Err(AppError::ValidationError(
+            "docker exec option is too long (max 4096 characters)".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validates an environment variable name from a connection's `env` map
+///
+/// Only conventional upper-case names are accepted so they cannot be mistaken for
+/// `docker exec` flags or expand differently when copied into a shell script.
+pub fn validate_env_key(key: &str) -> Result<(), AppError> {
+    let pattern = Regex::new(r"^[A-Z_][A-Z0-9_]*$").expect("Failed to compile regex");
+    if !pattern.is_match(key) {
+        return Err(AppError::ValidationError(format!(
+            "Invalid environment variable name '{}'. Use upper-case letters, digits, and '_', not starting with a digit",
+            key
+        )));
+    }
+
+    Ok(())
+}
+
+/// Validates a client option from a connection's `options`, passed as `--<key> <value>`
+///
+/// Keys are plain option names so the resulting flag cannot smuggle in a value (`=`) or
+/// change shape; values are single arguments without control characters.
+pub fn validate_client_option(key: &str, value: &str) -> Result<(), AppError> {
+    let pattern = Regex::new(r"^[A-Za-z][A-Za-z0-9_-]*$").expect("Failed to compile regex");
+    if !pattern.is_match(key) || key.len() > 64 {
+        return Err(AppError::ValidationError(format!(
+            "Invalid client option name '{}'. Use letters, digits, '_', and '-', starting with a letter",
+            key
+        )));
+    }
+
+    if value.chars().any(char::is_control) {
+        return Err(AppError::ValidationError(format!(
+            "Client option '{}' must not contain control characters",
+            key
+        )));
+    }
+
+    if value.len() > 4096 {
+        return Err(AppError::ValidationError(format!(
+            "Client option '{}' is too long (max 4096 characters)",
+            key
+        )));
+    }
+
+    Ok(())
+}
+
+/// Validates an extra argument passed verbatim to the database client
+pub fn validate_client_arg(arg: &str) -> Result<(), AppError> {
+    if arg.is_empty() {
+        return Err(AppError::ValidationError(
+            "Client argument must not be empty".to_string(),
+        ));
+    }
+
+    if arg.chars().any(char::is_control) {
+        return Err(AppError::ValidationError(format!(
+            "Client argument '{}' must not contain control characters",
+            arg.escape_debug()
+        )));
+    }
+
+    if arg.len() > 4096 {
+        return Err(AppError::ValidationError(
+            "Client argument is too long (max 4096 characters)".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validates a `pre_connect`/`post_connect` hook command
+///
+/// Hooks are shell commands by design, so this cannot make them safe; it only rejects empty
+/// commands and control characters (a multi-line script belongs in a file the hook runs).
+pub fn validate_hook_command(command: &str) -> Result<(), AppError> {
+    if command.trim().is_empty() {
+        return Err(AppError::ValidationError(
+            "Hook command must not be empty".to_string(),
+        ));
+    }
+
+    if command.chars().any(|c| c.is_control() && c != '\t') {
+        return Err(AppError::ValidationError(
+            "Hook command must be a single line without control characters".to_string(),
+        ));
+    }
+
+    if command.len() > 4096 {
+        return Err(AppError::ValidationError(
+            "Hook command is too long (max 4096 characters)".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks a port against the database type, returning an advisory when it looks like a typo
+///
+/// This never fails: containers are often remapped to other ports, so a mismatch is only
+/// worth a warning, e.g. PostgreSQL on 3306 (the MySQL default).
+pub fn validate_port_for_type(port: u16, db_type: &DatabaseType) -> Option<String> {
+    if port == 0 {
+        return Some("Port 0 is not a usable port".to_string());
+    }
+
+    let Some(default_port) = db_type.default_port() else {
+        return Some(format!(
+            "{} connections do not use a port; {} will be ignored",
+            db_type, port
+        ));
+    };
+    if port == default_port {
+        return None;
+    }
+
+    DatabaseType::ALL
+        .iter()
+        .find(|other| other.default_port() == Some(port))
+        .map(|other| {
+            format!(
+                "Port {} is the default {} port, but the connection is {} (default {})",
+                port, other, db_type, default_port
+            )
+        })
+}
+
+/// Print the advisory from [`validate_port_for_type`] as a warning, if any
+pub fn warn_port_for_type(port: Option<u16>, db_type: &DatabaseType) {
+    if let Some(advisory) = port.and_then(|port| validate_port_for_type(port, db_type)) {
+        eprintln!("Warning: {}", advisory);
+    }
+}
+
 /// Sanitizes input for safe shell usage
 pub fn sanitize_for_shell(input: &str) -> String {
     shell_escape::escape(input.into()).to_string()
@@ -68,6 +424,104 @@ pub fn sanitize_for_shell(input: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_validate_sqlite_path() {
+        assert!(validate_sqlite_path("/data/app.db").is_ok());
+        assert!(validate_sqlite_path("db/app.sqlite3").is_ok());
+        assert!(validate_sqlite_path("app.db").is_ok());
+        assert!(validate_sqlite_path("").is_err());
+        assert!(validate_sqlite_path("-cmd").is_err());
+        assert!(validate_sqlite_path("/data/app.db; rm -rf /").is_err());
+        // Slashes stay invalid for server database names
+        assert!(validate_database_name("data/app").is_err());
+    }
+
+    #[test]
+    fn test_validate_client_path() {
+        assert!(validate_client_path("/opt/pg/bin/psql").is_ok());
+        assert!(validate_client_path("mongo").is_ok());
+        assert!(validate_client_path("").is_err());
+        assert!(validate_client_path("--help").is_err());
+        assert!(validate_client_path("psql -c").is_err());
+    }
+
+    #[test]
+    fn test_validate_profile_name() {
+        assert!(validate_profile_name("work").is_ok());
+        assert!(validate_profile_name("side_project-2").is_ok());
+        assert!(validate_profile_name("").is_err());
+        assert!(validate_profile_name("../escape").is_err());
+        assert!(validate_profile_name("a.b").is_err());
+        assert!(validate_profile_name(&"p".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_validate_port_for_type() {
+        assert_eq!(
+            validate_port_for_type(5432, &DatabaseType::PostgreSQL),
+            None
+        );
+        // Non-default ports that belong to no other database are fine
+        assert_eq!(
+            validate_port_for_type(15432, &DatabaseType::PostgreSQL),
+            None
+        );
+
+        let advisory = validate_port_for_type(3306, &DatabaseType::PostgreSQL).unwrap();
+        assert!(advisory.contains("default MySQL port"));
+        assert!(advisory.contains("PostgreSQL (default 5432)"));
+
+        assert!(validate_port_for_type(27017, &DatabaseType::MySQL).is_some());
+        assert!(validate_port_for_type(5432, &DatabaseType::SQLite).is_some());
+        assert!(validate_port_for_type(0, &DatabaseType::MongoDB).is_some());
+    }
+
+    #[test]
+    fn test_validate_exec_option() {
+        assert!(validate_exec_option("-e").is_ok());
+        assert!(validate_exec_option("PGTZ=UTC").is_ok());
+        assert!(validate_exec_option("--env=LANG=C.UTF-8").is_ok());
+        assert!(validate_exec_option("--user=1000:1000").is_ok());
+        assert!(validate_exec_option("").is_err());
+        assert!(validate_exec_option("-e FOO=bar").is_err());
+        assert!(validate_exec_option("FOO=$(id)").is_err());
+        assert!(validate_exec_option("a;b").is_err());
+        assert!(validate_exec_option("a|b").is_err());
+        assert!(validate_exec_option("`id`").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bypass_allows_rejected_names() {
+        assert!(validate_container_name("-Weird Name!").is_err());
+
+        scope_bypass(async {
+            assert!(validate_container_name("-Weird Name!").is_ok());
+            assert!(validate_username("1user").is_ok());
+            assert!(validate_database_name("my-db").is_ok());
+            // Only container, username, and database names are bypassed
+            assert!(validate_alias("").is_err());
+        })
+        .await;
+
+        assert!(validate_container_name("-Weird Name!").is_err());
+    }
+
+    #[test]
+    fn test_validate_alias() {
+        assert!(validate_alias("pg-dev").is_ok());
+        assert!(validate_alias("mysql.dev_2").is_ok());
+        assert!(validate_alias("本番db").is_ok());
+
+        assert!(validate_alias("").is_err());
+        assert!(validate_alias("a b").is_err());
+        assert!(validate_alias("../escape").is_err());
+        assert!(validate_alias("a/b").is_err());
+        assert!(validate_alias("key: value").is_err());
+        assert!(validate_alias("-pg").is_err());
+        assert!(validate_alias(".hidden").is_err());
+        assert!(validate_alias("tab\there").is_err());
+    }
+
     #[test]
     fn test_valid_container_names() {
         assert!(validate_container_name("mysql").is_ok());
@@ -85,6 +539,21 @@ mod tests {
         assert!(validate_container_name("").is_err());
     }
 
+    #[test]
+    fn test_compose_and_swarm_container_names() {
+        assert!(validate_container_name("myproject-db-1").is_ok());
+        assert!(validate_container_name("myproject_db_1").is_ok());
+        assert!(validate_container_name("stack_db.1.abcxyz0123456789").is_ok());
+        assert!(validate_container_name("3f4e8a9b2c1d").is_ok());
+        assert!(validate_container_name("/myproject-db-1").is_ok());
+
+        // Flags and path-like names are still rejected
+        assert!(validate_container_name("-it").is_err());
+        assert!(validate_container_name("/-it").is_err());
+        assert!(validate_container_name("//db").is_err());
+        assert!(validate_container_name("stack/db").is_err());
+    }
+
     #[test]
     fn test_valid_usernames() {
         assert!(validate_username("root").is_ok());
@@ -101,6 +570,64 @@ mod tests {
         assert!(validate_username("").is_err());
     }
 
+    #[test]
+    fn test_validate_working_dir() {
+        assert!(validate_working_dir("/").is_ok());
+        assert!(validate_working_dir("/var/lib/app data").is_ok());
+        assert!(validate_working_dir("relative/dir").is_err());
+        assert!(validate_working_dir("-w").is_err());
+        assert!(validate_working_dir("/tmp\nrm").is_err());
+    }
+
+    #[test]
+    fn test_validate_env_key() {
+        assert!(validate_env_key("PGTZ").is_ok());
+        assert!(validate_env_key("_PRIVATE_1").is_ok());
+        assert!(validate_env_key("pgtz").is_err());
+        assert!(validate_env_key("1ABC").is_err());
+        assert!(validate_env_key("-e").is_err());
+        assert!(validate_env_key("A=B").is_err());
+        assert!(validate_env_key("").is_err());
+    }
+
+    #[test]
+    fn test_validate_container_id() {
+        assert!(validate_container_id("3f2a").is_ok());
+        assert!(validate_container_id(&"a".repeat(64)).is_ok());
+        assert!(validate_container_id(&"a".repeat(65)).is_err());
+        assert!(validate_container_id("3F2A").is_err());
+        assert!(validate_container_id("pg-dev").is_err());
+        assert!(validate_container_id("").is_err());
+    }
+
+    #[test]
+    fn test_validate_hook_command() {
+        assert!(validate_hook_command("ssh -fN -L 5433:localhost:5432 bastion").is_ok());
+        assert!(validate_hook_command("pkill -f 'ssh -fN'").is_ok());
+        assert!(validate_hook_command("  ").is_err());
+        assert!(validate_hook_command("echo a\necho b").is_err());
+        assert!(validate_hook_command(&"x".repeat(4097)).is_err());
+    }
+
+    #[test]
+    fn test_validate_client_option() {
+        assert!(validate_client_option("connect-timeout", "5").is_ok());
+        assert!(validate_client_option("set", "ON_ERROR_STOP=1").is_ok());
+        assert!(validate_client_option("init-command", "SET NAMES utf8mb4").is_ok());
+        assert!(validate_client_option("-e", "DROP DATABASE app").is_err());
+        assert!(validate_client_option("host=evil", "x").is_err());
+        assert!(validate_client_option("key", "a\nb").is_err());
+        assert!(validate_client_option("", "x").is_err());
+    }
+
+    #[test]
+    fn test_validate_client_arg() {
+        assert!(validate_client_arg("--table").is_ok());
+        assert!(validate_client_arg("-P").is_ok());
+        assert!(validate_client_arg("").is_err());
+        assert!(validate_client_arg("-c\nDROP").is_err());
+    }
+
     #[test]
     fn test_sanitize_shell() {
         // Normal strings should remain unchanged